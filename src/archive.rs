@@ -0,0 +1,288 @@
+//! Reading Python sources straight out of wheels, sdists and zip files.
+//!
+//! Members are read into memory, never extracted, and reported under
+//! `archive!inner/path.py`. Archives inside archives are opened in turn, so a
+//! zip holding an sdist yields paths like `bundle.zip!pkg-1.0.tar.gz!pkg-1.0/pkg/mod.py`.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+use crate::discover;
+use crate::imports::ModulePath;
+use crate::source::SourceFile;
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl Kind {
+    fn of(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if [".whl", ".zip", ".egg"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+        {
+            Some(Kind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Kind::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Kind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// What could be read out of an archive.
+#[derive(Debug, Default)]
+pub struct Contents {
+    pub sources: Vec<(ModulePath, SourceFile)>,
+    /// Nested archives that couldn't be read and were skipped.
+    pub errors: Vec<io::Error>,
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    Kind::of(&path.to_string_lossy()).is_some()
+}
+
+/// The Python sources in the archive at `path`, with the module names they
+/// would be importable under once installed or unpacked.
+///
+/// Members that aren't valid UTF-8 or contain NUL bytes are skipped as binary.
+/// Nested archives that can't be read are skipped too, with their errors in
+/// [`Contents::errors`].
+pub fn read(path: &Path) -> io::Result<Contents> {
+    let display = path.display().to_string();
+    let kind = Kind::of(&display).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{display}: not a supported archive"),
+        )
+    })?;
+    let mut contents = Contents::default();
+    File::open(path)
+        .and_then(|file| read_archive(kind, BufReader::new(file), &display, &mut contents))
+        .map_err(|err| io::Error::new(err.kind(), format!("{display}: {err}")))?;
+    Ok(contents)
+}
+
+fn read_archive<R: Read + Seek>(
+    kind: Kind,
+    reader: R,
+    prefix: &str,
+    contents: &mut Contents,
+) -> io::Result<()> {
+    let mut files = Vec::new();
+    match kind {
+        Kind::Zip => {
+            let mut archive = ZipArchive::new(reader)?;
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index)?;
+                if entry.is_file() {
+                    let name = entry.name().to_owned();
+                    read_member(&name, &mut entry, prefix, &mut files, contents)?;
+                }
+            }
+        }
+        Kind::Tar => read_tar(tar::Archive::new(reader), prefix, &mut files, contents)?,
+        Kind::TarGz => read_tar(
+            tar::Archive::new(GzDecoder::new(reader)),
+            prefix,
+            &mut files,
+            contents,
+        )?,
+    }
+    let packages: HashSet<&str> = files
+        .iter()
+        .filter_map(|(name, _)| {
+            name.strip_suffix("/__init__.py")
+                .or_else(|| name.strip_suffix("/__init__.pyi"))
+        })
+        .collect();
+    for (name, text) in &files {
+        let module_path = ModulePath::with_packages(Path::new(name), |dir| {
+            dir.to_str().is_some_and(|dir| packages.contains(dir))
+        });
+        contents.sources.push((
+            module_path,
+            SourceFile::new(format!("{prefix}!{name}"), text),
+        ));
+    }
+    Ok(())
+}
+
+fn read_tar<R: Read>(
+    mut archive: tar::Archive<R>,
+    prefix: &str,
+    files: &mut Vec<(String, String)>,
+    contents: &mut Contents,
+) -> io::Result<()> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let name = name.trim_start_matches("./").to_owned();
+        read_member(&name, &mut entry, prefix, files, contents)?;
+    }
+    Ok(())
+}
+
+/// Collects `name` into `files` if it's a Python source, or opens it if it's
+/// a nested archive. A nested archive that can't be read adds nothing but
+/// its error to `contents`, and the outer archive is read on.
+fn read_member(
+    name: &str,
+    reader: &mut dyn Read,
+    prefix: &str,
+    files: &mut Vec<(String, String)>,
+    contents: &mut Contents,
+) -> io::Result<()> {
+    let mut dirs = name.split('/').rev().skip(1);
+    if dirs.any(discover::is_skipped_dir) {
+        return Ok(());
+    }
+    if let Some(kind) = Kind::of(name) {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let prefix = format!("{prefix}!{name}");
+        let mut nested = Contents::default();
+        match read_archive(kind, Cursor::new(bytes), &prefix, &mut nested) {
+            Ok(()) => {
+                contents.sources.append(&mut nested.sources);
+                contents.errors.append(&mut nested.errors);
+            }
+            Err(err) => contents
+                .errors
+                .push(io::Error::new(err.kind(), format!("{prefix}: {err}"))),
+        }
+        Ok(())
+    } else if discover::is_python_file(Path::new(name)) {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        if let Ok(text) = String::from_utf8(bytes) {
+            if !text.contains('\0') {
+                files.push((name.to_owned(), text));
+            }
+        }
+        Ok(())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::{FileOptions, ZipWriter};
+
+    use super::*;
+
+    fn zip(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in members {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tar(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, bytes) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *bytes).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn read_zip(bytes: Vec<u8>) -> Contents {
+        let mut contents = Contents::default();
+        read_archive(Kind::Zip, Cursor::new(bytes), "a.whl", &mut contents).unwrap();
+        contents
+    }
+
+    fn paths(contents: &Contents) -> Vec<(String, String)> {
+        contents
+            .sources
+            .iter()
+            .map(|(module, source)| (module.name.clone(), source.path().to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn members_and_module_names() {
+        let contents = read_zip(zip(&[
+            ("pkg/__init__.py", b""),
+            ("pkg/models.py", b"x = 1\n"),
+            ("pkg/data.bin", b"\0\x01"),
+            ("pkg/blob.py", b"\xff\xfe"),
+            ("pkg/__pycache__/models.py", b""),
+            ("pkg-1.0.dist-info/METADATA", b"Name: pkg\n"),
+        ]));
+        assert_eq!(
+            paths(&contents),
+            [
+                ("pkg".to_owned(), "a.whl!pkg/__init__.py".to_owned()),
+                ("pkg.models".to_owned(), "a.whl!pkg/models.py".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_archives() {
+        let sdist = tar(&[
+            ("pkg-1.0/pkg/__init__.py", b""),
+            ("pkg-1.0/pkg/mod.py", b"y = 2\n"),
+        ]);
+        let contents = read_zip(zip(&[("pkg-1.0.tar", &sdist)]));
+        assert_eq!(
+            paths(&contents),
+            [
+                (
+                    "pkg".to_owned(),
+                    "a.whl!pkg-1.0.tar!pkg-1.0/pkg/__init__.py".to_owned()
+                ),
+                (
+                    "pkg.mod".to_owned(),
+                    "a.whl!pkg-1.0.tar!pkg-1.0/pkg/mod.py".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_nested_archives_are_skipped() {
+        let contents = read_zip(zip(&[
+            ("vendor/broken.whl", b"not a zip"),
+            ("pkg/__init__.py", b""),
+        ]));
+        assert_eq!(
+            paths(&contents),
+            [("pkg".to_owned(), "a.whl!pkg/__init__.py".to_owned())]
+        );
+        assert_eq!(contents.errors.len(), 1);
+        assert!(contents.errors[0]
+            .to_string()
+            .starts_with("a.whl!vendor/broken.whl: "));
+    }
+
+    #[test]
+    fn kinds() {
+        assert!(is_archive(Path::new("dist/pkg-1.0-py3-none-any.WHL")));
+        assert!(is_archive(Path::new("pkg-1.0.tar.gz")));
+        assert!(!is_archive(Path::new("pkg/tar.py")));
+    }
+}
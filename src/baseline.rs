@@ -0,0 +1,256 @@
+//! Accepting existing findings so that only new ones are reported.
+//!
+//! A baseline records each finding by its rule, its file relative to the
+//! settings' root, the dotted path of the innermost enclosing class (or the
+//! module, outside classes) and a fingerprint of the reported line's content.
+//! Line numbers are kept only for display, so findings still match after
+//! code around them moves.
+//! Identical keys are matched by count: a baseline with one `Bar(Foo)`
+//! finding accepts one such finding, and a copy of it is new.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostic::Diagnostic;
+use crate::discover;
+use crate::error::{Error, Result};
+use crate::mine;
+use crate::project::Project;
+
+/// Version 1 recorded paths as reported rather than relative to the root.
+const VERSION: u32 = 2;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    version: u32,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub code: String,
+    /// The dotted name of the module the finding is in.
+    pub module: String,
+    /// `package.module.Class` for findings in a class, `package.module`
+    /// otherwise.
+    pub symbol: String,
+    /// SHA-256 of the reported line without surrounding whitespace.
+    pub fingerprint: String,
+    /// The file, relative to the settings' root with `/` separators, or as
+    /// reported if it's outside the root.
+    pub path: String,
+    /// Where the finding was when the baseline was written.
+    pub line: usize,
+}
+
+impl Entry {
+    /// What identifies the finding, leaving out where it is in the file.
+    fn key(&self) -> (String, String, String, String) {
+        (
+            self.code.clone(),
+            self.path.clone(),
+            self.symbol.clone(),
+            self.fingerprint.clone(),
+        )
+    }
+}
+
+impl Baseline {
+    /// A baseline accepting every one of `diagnostics`, with paths relative
+    /// to `root`.
+    pub fn new(project: &Project, diagnostics: &[Diagnostic], root: &Path) -> Self {
+        let mut entries: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| entry(project, diagnostic, root))
+            .collect();
+        entries.sort();
+        Baseline {
+            version: VERSION,
+            entries,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let error = |message: String| Error::Baseline {
+            path: path.display().to_string(),
+            message,
+        };
+        let text = std::fs::read_to_string(path)?;
+        let baseline: Baseline =
+            serde_json::from_str(&text).map_err(|err| error(err.to_string()))?;
+        if baseline.version != VERSION {
+            return Err(error(format!(
+                "unsupported baseline version {}, expected {VERSION}",
+                baseline.version
+            )));
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        json.push('\n');
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Drops the diagnostics the baseline accepts, returning the entries that
+    /// matched nothing because the finding has since been fixed. Entries in
+    /// modules that weren't loaded, because they're outside the checked paths
+    /// or failed to parse, are neither, and neither are those that `is_run`
+    /// says are for a rule that wasn't run on their file.
+    pub fn filter<'a>(
+        &'a self,
+        project: &Project,
+        diagnostics: &mut Vec<Diagnostic>,
+        root: &Path,
+        is_run: impl Fn(&Entry) -> bool,
+    ) -> Vec<&'a Entry> {
+        let loaded: HashSet<&str> = project
+            .modules()
+            .map(|(_, module)| module.name.as_str())
+            .collect();
+        let mut remaining: HashMap<_, Vec<&Entry>> = HashMap::new();
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| loaded.contains(entry.module.as_str()) && is_run(entry))
+        {
+            remaining.entry(entry.key()).or_default().push(entry);
+        }
+        diagnostics.retain(|diagnostic| {
+            let entry = entry(project, diagnostic, root);
+            remaining.get_mut(&entry.key()).and_then(Vec::pop).is_none()
+        });
+        let mut fixed: Vec<_> = remaining.into_values().flatten().collect();
+        fixed.sort();
+        fixed
+    }
+}
+
+fn entry(project: &Project, diagnostic: &Diagnostic, root: &Path) -> Entry {
+    let module = project
+        .modules()
+        .map(|(_, module)| module)
+        .find(|module| module.source.path() == diagnostic.path)
+        .expect("diagnostics are reported in loaded modules");
+    let symbol = match module.innermost_class(diagnostic.location) {
+        Some(class) => format!("{}.{}", module.name, class.qualname),
+        None => module.name.clone(),
+    };
+    Entry {
+        code: diagnostic.rule.code().to_owned(),
+        module: module.name.clone(),
+        symbol,
+        fingerprint: mine::fingerprint(module.source.line(diagnostic.location.line).trim()),
+        path: discover::relative_path(root, &diagnostic.path)
+            .unwrap_or_else(|| diagnostic.path.clone()),
+        line: diagnostic.location.line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::tests::project;
+    use crate::rules::{self, Rule};
+
+    const MODELS: &str = "from typing import NamedTuple\nclass Foo(NamedTuple):\n    x: int\n";
+
+    fn check(project: &Project) -> Vec<Diagnostic> {
+        rules::check(project, &[Rule::NamedTupleSubclass])
+    }
+
+    fn filter<'a>(
+        baseline: &'a Baseline,
+        project: &Project,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Vec<&'a Entry> {
+        baseline.filter(project, diagnostics, Path::new(""), |_| true)
+    }
+
+    #[test]
+    fn findings_match_after_moving() {
+        let before = project(&[("m", false, &format!("{MODELS}class Bar(Foo): pass\n"))]);
+        let baseline = Baseline::new(&before, &check(&before), Path::new(""));
+        let after = project(&[(
+            "m",
+            false,
+            &format!(
+                "{MODELS}\n\nclass Other: pass\n\nclass Bar(Foo): pass\nclass Baz(Foo): pass\n"
+            ),
+        )]);
+        let mut diagnostics = check(&after);
+        let fixed = filter(&baseline, &after, &mut diagnostics);
+        assert!(fixed.is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].location.line, 9);
+    }
+
+    #[test]
+    fn copies_are_new() {
+        let text = format!("{MODELS}class Bar(Foo): pass\n");
+        let before = project(&[("m", false, &text)]);
+        let baseline = Baseline::new(&before, &check(&before), Path::new(""));
+        let after = project(&[(
+            "m",
+            false,
+            &format!("{text}if True:\n    class Bar(Foo): pass\n"),
+        )]);
+        let mut diagnostics = check(&after);
+        filter(&baseline, &after, &mut diagnostics);
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn fixed_only_in_loaded_modules() {
+        let subclass = "from m import Foo\nclass Bar(Foo): pass\n";
+        let before = project(&[
+            ("m", false, MODELS),
+            ("a", false, subclass),
+            ("b", false, subclass),
+        ]);
+        let baseline = Baseline::new(&before, &check(&before), Path::new(""));
+        assert_eq!(baseline.entries.len(), 2);
+        let after = project(&[("m", false, MODELS), ("a", false, "")]);
+        let mut diagnostics = check(&after);
+        let fixed = filter(&baseline, &after, &mut diagnostics);
+        let fixed: Vec<&str> = fixed.iter().map(|entry| entry.symbol.as_str()).collect();
+        assert_eq!(fixed, ["a.Bar"]);
+    }
+
+    #[test]
+    fn same_module_names_in_different_files() {
+        let models = format!("{MODELS}class Bar(Foo): pass\n");
+        let before = project(&[("a.whl!models", false, &models), ("b.whl!m", false, "")]);
+        let baseline = Baseline::new(&before, &check(&before), Path::new(""));
+        assert_eq!(baseline.entries[0].path, "a.whl!models.py");
+        let after = project(&[
+            ("a.whl!models", false, MODELS),
+            ("b.whl!models", false, &models),
+        ]);
+        let mut diagnostics = check(&after);
+        let fixed = filter(&baseline, &after, &mut diagnostics);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "b.whl!models.py");
+        let fixed: Vec<&str> = fixed.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(fixed, ["a.whl!models.py"]);
+    }
+
+    #[test]
+    fn fixed_only_for_rules_that_ran() {
+        let before = project(&[("m", false, &format!("{MODELS}class Bar(Foo): pass\n"))]);
+        let baseline = Baseline::new(&before, &check(&before), Path::new(""));
+        let after = project(&[("m", false, MODELS)]);
+        let mut diagnostics = Vec::new();
+        let is_run = |entry: &Entry| entry.code != "NTS001";
+        assert!(baseline
+            .filter(&after, &mut diagnostics, Path::new(""), is_run)
+            .is_empty());
+        assert_eq!(filter(&baseline, &after, &mut diagnostics).len(), 1);
+    }
+}
@@ -0,0 +1,362 @@
+//! Settings from the `[tool.check-namedtuple-subclasses]` table in
+//! `pyproject.toml`.
+//!
+//! ```toml
+//! [tool.check-namedtuple-subclasses]
+//! select = ["NTS001", "NTS003"]
+//! extend-select = ["NTS002"]
+//! ignore = ["NTS005"]
+//! include = ["src/**/*.py"]
+//! exclude = ["src/vendor"]
+//! target-version = "3.9..3.13"
+//!
+//! [[tool.check-namedtuple-subclasses.overrides]]
+//! paths = ["tests"]
+//! ignore = ["NTS001"]
+//! ```
+//!
+//! Globs are matched against paths relative to the directory holding
+//! `pyproject.toml`; a pattern matching a directory covers everything in it.
+//! Overrides apply in order to the files they match, after the top-level
+//! selection and any command line options.
+
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+use crate::rules::Rule;
+use crate::runtime::VersionRange;
+
+const TABLE: &str = "check-namedtuple-subclasses";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Options {
+    select: Option<Vec<String>>,
+    #[serde(default)]
+    extend_select: Vec<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    include: Option<Vec<String>>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    overrides: Vec<OverrideOptions>,
+    target_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct OverrideOptions {
+    paths: Vec<String>,
+    select: Option<Vec<String>>,
+    #[serde(default)]
+    extend_select: Vec<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The directory globs are relative to.
+    root: PathBuf,
+    select: Option<Vec<Rule>>,
+    extend_select: Vec<Rule>,
+    ignore: Vec<Rule>,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    overrides: Vec<Override>,
+    target_version: Option<VersionRange>,
+}
+
+#[derive(Debug)]
+struct Override {
+    paths: GlobSet,
+    select: Option<Vec<Rule>>,
+    extend_select: Vec<Rule>,
+    ignore: Vec<Rule>,
+}
+
+impl Config {
+    /// The settings in the closest `pyproject.toml` at or above `start` that
+    /// has a `[tool.check-namedtuple-subclasses]` table, or the defaults.
+    pub fn find(start: &Path) -> Result<Config> {
+        let start = start.canonicalize()?;
+        for dir in start.ancestors() {
+            let path = dir.join("pyproject.toml");
+            if path.is_file() {
+                if let Some(config) = Config::load(&path)? {
+                    return Ok(config);
+                }
+            }
+        }
+        Ok(Config {
+            root: start,
+            ..Config::default()
+        })
+    }
+
+    /// Like [`Config::find`], starting from the deepest directory holding all
+    /// of `paths`, so that settings apply the same wherever the tool runs.
+    pub fn find_for(paths: &[PathBuf]) -> Result<Config> {
+        let mut common: Option<PathBuf> = None;
+        for path in paths {
+            // Missing paths are reported when files are discovered.
+            let Ok(path) = path.canonicalize() else {
+                continue;
+            };
+            let dir = if path.is_dir() {
+                path
+            } else {
+                path.parent().map_or(path.clone(), Path::to_path_buf)
+            };
+            common = Some(match common {
+                Some(common) => common
+                    .ancestors()
+                    .find(|ancestor| dir.starts_with(ancestor))
+                    .map_or(common.clone(), Path::to_path_buf),
+                None => dir,
+            });
+        }
+        Config::find(common.as_deref().unwrap_or(Path::new(".")))
+    }
+
+    /// The settings in the `pyproject.toml` at `path`, or `None` if it has no
+    /// table for this tool.
+    pub fn load(path: &Path) -> Result<Option<Config>> {
+        let display = path.display().to_string();
+        let error = |message: String| Error::Config {
+            path: display.clone(),
+            message,
+        };
+        let text = std::fs::read_to_string(path)?;
+        let document: toml::Table = toml::from_str(&text).map_err(|err| error(err.to_string()))?;
+        let Some(table) = document
+            .get("tool")
+            .and_then(|tool| tool.get(TABLE))
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        let options: Options = table
+            .try_into()
+            .map_err(|err: toml::de::Error| error(format!("[tool.{TABLE}]: {}", err.message())))?;
+        let rules = |names: &[String]| -> Result<Vec<Rule>> {
+            names
+                .iter()
+                .map(|name| name.parse().map_err(error))
+                .collect()
+        };
+        let globs = |patterns: &[String]| -> Result<GlobSet> {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in patterns {
+                builder.add(Glob::new(pattern).map_err(|err| error(err.to_string()))?);
+            }
+            builder.build().map_err(|err| error(err.to_string()))
+        };
+        let path = path.canonicalize()?;
+        let root = path.parent().unwrap_or(&path).to_path_buf();
+        Ok(Some(Config {
+            root,
+            select: options.select.as_deref().map(rules).transpose()?,
+            extend_select: rules(&options.extend_select)?,
+            ignore: rules(&options.ignore)?,
+            include: options.include.as_deref().map(globs).transpose()?,
+            exclude: Some(globs(&options.exclude)?),
+            overrides: options
+                .overrides
+                .iter()
+                .map(|option| {
+                    Ok(Override {
+                        paths: globs(&option.paths)?,
+                        select: option.select.as_deref().map(rules).transpose()?,
+                        extend_select: rules(&option.extend_select)?,
+                        ignore: rules(&option.ignore)?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            target_version: options
+                .target_version
+                .map(|versions| versions.parse().map_err(error))
+                .transpose()?,
+        }))
+    }
+
+    /// The rules to run everywhere: `select` from the command line or else
+    /// from the settings, plus both `extend-select` lists, minus `ignore`.
+    pub fn selection(&self, select: Option<Vec<Rule>>, extend_select: &[Rule]) -> Vec<Rule> {
+        let mut selected = select
+            .or_else(|| self.select.clone())
+            .unwrap_or_else(Rule::defaults);
+        selected.extend(&self.extend_select);
+        selected.extend(extend_select);
+        selected.retain(|rule| !self.ignore.contains(rule));
+        selected
+    }
+
+    /// The rules to run on `path`, starting from the overall `selected` ones.
+    pub fn rules_for(&self, selected: &[Rule], path: &Path) -> Vec<Rule> {
+        let relative = self.relative(path);
+        let mut rules = selected.to_vec();
+        for option in &self.overrides {
+            if !matches_or_ancestor(&option.paths, &relative) {
+                continue;
+            }
+            if let Some(select) = &option.select {
+                rules.clone_from(select);
+            }
+            rules.extend(&option.extend_select);
+            rules.retain(|rule| !option.ignore.contains(rule));
+        }
+        rules
+    }
+
+    /// The directory holding the settings, or else the deepest one holding
+    /// the checked paths.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The Python versions to describe runtime behaviour for, if set.
+    pub fn target_version(&self) -> Option<VersionRange> {
+        self.target_version
+    }
+
+    /// Whether a discovered file is checked, according to `include` and
+    /// `exclude`.
+    pub fn is_included(&self, path: &Path) -> bool {
+        let relative = self.relative(path);
+        let included = self
+            .include
+            .as_ref()
+            .map_or(true, |include| matches_or_ancestor(include, &relative));
+        let excluded = self
+            .exclude
+            .as_ref()
+            .is_some_and(|exclude| matches_or_ancestor(exclude, &relative));
+        included && !excluded
+    }
+
+    /// `path` relative to the settings' directory. Members of archives,
+    /// `archive!inner/path.py`, are matched by the archive's path.
+    fn relative(&self, path: &Path) -> PathBuf {
+        let on_disk = path.to_string_lossy();
+        let on_disk = Path::new(on_disk.split('!').next().unwrap_or_default());
+        on_disk
+            .canonicalize()
+            .ok()
+            .and_then(|path| path.strip_prefix(&self.root).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| on_disk.to_path_buf())
+    }
+}
+
+fn matches_or_ancestor(globs: &GlobSet, path: &Path) -> bool {
+    path.ancestors()
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| globs.is_match(ancestor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory holding `files`, given as relative path and content.
+    fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "check-namedtuple-config-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, text) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+        root
+    }
+
+    const PYPROJECT: &str = r#"
+[tool.check-namedtuple-subclasses]
+extend-select = ["NTS002"]
+ignore = ["NTS005"]
+exclude = ["src/vendor"]
+
+[[tool.check-namedtuple-subclasses.overrides]]
+paths = ["tests"]
+ignore = ["NTS001"]
+"#;
+
+    #[test]
+    fn selection_and_overrides() {
+        let root = tree(
+            "overrides",
+            &[
+                ("pyproject.toml", PYPROJECT),
+                ("src/app.py", ""),
+                ("src/vendor/lib.py", ""),
+                ("tests/test_app.py", ""),
+            ],
+        );
+        let config = Config::find(&root.join("src")).unwrap();
+        let selected = config.selection(None, &[]);
+        assert_eq!(
+            selected,
+            [
+                Rule::NamedTupleSubclass,
+                Rule::SuperInNamedTuple,
+                Rule::NamedTupleMetaclass,
+                Rule::CollectionsNamedTupleSubclass,
+            ]
+        );
+        assert_eq!(
+            config.selection(Some(vec![Rule::NamedTupleMultipleInheritance]), &[]),
+            [Rule::CollectionsNamedTupleSubclass]
+        );
+        let in_tests = config.rules_for(&selected, &root.join("tests/test_app.py"));
+        assert!(!in_tests.contains(&Rule::NamedTupleSubclass));
+        assert!(in_tests.contains(&Rule::SuperInNamedTuple));
+        assert_eq!(
+            config.rules_for(&selected, &root.join("src/app.py")),
+            selected
+        );
+        assert!(config.is_included(&root.join("src/app.py")));
+        assert!(!config.is_included(&root.join("src/vendor/lib.py")));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn found_from_the_checked_paths() {
+        let root = tree(
+            "find-for",
+            &[
+                ("project/pyproject.toml", PYPROJECT),
+                ("project/src/a.py", ""),
+                ("project/tests/b.py", ""),
+                ("other/pyproject.toml", "[tool.black]\n"),
+            ],
+        );
+        let project = root.join("project");
+        let paths = [project.join("src/a.py"), project.join("tests")];
+        let config = Config::find_for(&paths).unwrap();
+        assert_eq!(config.root, project.canonicalize().unwrap());
+        let config = Config::find_for(&[root.join("other")]).unwrap();
+        assert_eq!(config.selection(None, &[]), Rule::defaults());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn unknown_rules_are_errors() {
+        let root = tree(
+            "unknown",
+            &[(
+                "pyproject.toml",
+                "[tool.check-namedtuple-subclasses]\nselect = [\"NTS999\"]\n",
+            )],
+        );
+        let err = Config::load(&root.join("pyproject.toml")).unwrap_err();
+        assert!(err.to_string().contains("unknown rule `NTS999`"), "{err}");
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
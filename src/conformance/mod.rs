@@ -0,0 +1,61 @@
+//! Test cases for the proposed "typed named tuples are implicitly final" rule,
+//! laid out like the typing specification's conformance suite.
+//!
+//! Every line a type checker must report ends in `# E`, optionally followed by
+//! `: <description>`, and every line it may report ends in `# E?`. Type
+//! checker authors record their results in `results/<checker>/<test>.toml`,
+//! in the shape described by `results/results-schema.json`.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One generated test file.
+#[derive(Debug, Clone, Copy)]
+pub struct Case {
+    pub file_name: &'static str,
+    pub source: &'static str,
+}
+
+pub const CASES: &[Case] = &[
+    Case {
+        file_name: "namedtuples_final_direct.py",
+        source: include_str!("tests/namedtuples_final_direct.py"),
+    },
+    Case {
+        file_name: "namedtuples_final_generic.py",
+        source: include_str!("tests/namedtuples_final_generic.py"),
+    },
+    Case {
+        file_name: "namedtuples_final_functional.py",
+        source: include_str!("tests/namedtuples_final_functional.py"),
+    },
+    Case {
+        file_name: "namedtuples_final_type_call.py",
+        source: include_str!("tests/namedtuples_final_type_call.py"),
+    },
+    Case {
+        file_name: "namedtuples_final_stub.pyi",
+        source: include_str!("tests/namedtuples_final_stub.pyi"),
+    },
+];
+
+pub const RESULTS_SCHEMA: &str = include_str!("results-schema.json");
+
+/// Writes the cases to `dir/tests/` and the schema to `dir/results/`,
+/// returning the paths written.
+pub fn write(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let tests = dir.join("tests");
+    let results = dir.join("results");
+    std::fs::create_dir_all(&tests)?;
+    std::fs::create_dir_all(&results)?;
+    let mut written = Vec::new();
+    for case in CASES {
+        let path = tests.join(case.file_name);
+        std::fs::write(&path, case.source)?;
+        written.push(path);
+    }
+    let path = results.join("results-schema.json");
+    std::fs::write(&path, RESULTS_SCHEMA)?;
+    written.push(path);
+    Ok(written)
+}
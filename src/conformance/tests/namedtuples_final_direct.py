@@ -0,0 +1,46 @@
+"""
+Tests that a class statement can't inherit from a NamedTuple class.
+"""
+
+# Specification (proposed): NamedTuple classes are implicitly final.
+# https://typing.readthedocs.io/en/latest/spec/namedtuples.html
+
+from typing import NamedTuple, final
+
+
+class Point(NamedTuple):
+    x: int
+    y: int
+
+
+class Point3D(Point):  # E: NamedTuple class "Point" is implicitly final
+    z: int
+
+
+class PointWithMethods(Point):  # E: NamedTuple class "Point" is implicitly final
+    def norm(self) -> float:
+        return (self.x**2 + self.y**2) ** 0.5
+
+
+class EmptySubclass(Point):  # E: NamedTuple class "Point" is implicitly final
+    pass
+
+
+@final
+class ExplicitlyFinal(NamedTuple):
+    x: int
+
+
+class SubclassOfExplicitlyFinal(ExplicitlyFinal):  # E: final class
+    pass
+
+
+# Inheriting directly from NamedTuple defines a new named tuple and is allowed.
+class Vector(NamedTuple):
+    dx: int
+    dy: int
+
+
+# Named tuples are still tuples.
+def as_tuple(p: Point) -> tuple[int, int]:
+    return p
@@ -0,0 +1,30 @@
+"""
+Tests that NamedTuple types created with the functional syntax can't be
+inherited from.
+"""
+
+# Specification (proposed): NamedTuple classes are implicitly final.
+# https://typing.readthedocs.io/en/latest/spec/namedtuples.html
+
+from collections import namedtuple
+from typing import NamedTuple
+
+Point = NamedTuple("Point", [("x", int), ("y", int)])
+
+
+class Point3D(Point):  # E: NamedTuple class "Point" is implicitly final
+    z: int
+
+
+class Inline(NamedTuple("Inline", [("x", int)])):  # E: NamedTuple class "Inline" is implicitly final
+    pass
+
+
+# Subclassing collections.namedtuple types is a documented idiom and stays
+# allowed.
+Untyped = namedtuple("Untyped", ["x", "y"])
+
+
+class UntypedWithMethods(Untyped):
+    def total(self) -> int:
+        return self.x + self.y
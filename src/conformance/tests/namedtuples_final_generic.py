@@ -0,0 +1,41 @@
+"""
+Tests that generic NamedTuple classes can't be inherited from, whether or not
+the base is specialized.
+"""
+
+# Specification (proposed): NamedTuple classes are implicitly final.
+# https://typing.readthedocs.io/en/latest/spec/namedtuples.html
+
+from typing import Generic, NamedTuple, TypeVar
+
+T = TypeVar("T")
+
+
+class Pair(NamedTuple, Generic[T]):
+    first: T
+    second: T
+
+
+class IntPair(Pair[int]):  # E: NamedTuple class "Pair" is implicitly final
+    pass
+
+
+class UnspecializedPair(Pair):  # E: NamedTuple class "Pair" is implicitly final
+    pass
+
+
+class Box[U](NamedTuple):
+    value: U
+
+
+class StrBox(Box[str]):  # E: NamedTuple class "Box" is implicitly final
+    pass
+
+
+class GenericBox[V](Box[V]):  # E: NamedTuple class "Box" is implicitly final
+    pass
+
+
+# Specializing without subclassing is allowed.
+IntBox = Box[int]
+b: IntBox = Box(1)
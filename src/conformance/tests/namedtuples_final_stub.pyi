@@ -0,0 +1,22 @@
+"""
+Tests that stubs can't declare classes inheriting from NamedTuple classes.
+"""
+
+# Specification (proposed): NamedTuple classes are implicitly final.
+# https://typing.readthedocs.io/en/latest/spec/namedtuples.html
+
+# Stubs often describe a runtime subclass of a private named tuple this way,
+# e.g. `urllib.parse.ParseResult` in typeshed.
+
+from typing import NamedTuple
+
+class _VersionBase(NamedTuple):
+    major: int
+    minor: int
+
+class Version(_VersionBase):  # E: NamedTuple class "_VersionBase" is implicitly final
+    def __ge__(self, other: tuple[int, ...]) -> bool: ...
+
+class Release(NamedTuple):
+    version: Version
+    name: str
@@ -0,0 +1,26 @@
+"""
+Tests subclasses of NamedTuple classes created at runtime with type() or
+types.new_class().
+"""
+
+# Specification (proposed): NamedTuple classes are implicitly final.
+# https://typing.readthedocs.io/en/latest/spec/namedtuples.html
+
+# The class statement rule doesn't cover dynamically created classes, so a
+# type checker may, but need not, report them.
+
+import types
+from typing import NamedTuple
+
+
+class Point(NamedTuple):
+    x: int
+    y: int
+
+
+Point3D = type("Point3D", (Point,), {})  # E?: NamedTuple class "Point" is implicitly final
+
+Point4D = types.new_class("Point4D", (Point,))  # E?: NamedTuple class "Point" is implicitly final
+
+# A type() call with a single argument returns the type of an object.
+t = type(Point(1, 2))
@@ -0,0 +1,82 @@
+use std::fmt;
+
+use crate::intent::Intent;
+use crate::project::Factory;
+use crate::rules::Rule;
+use crate::runtime::{Behaviour, VersionOutcome};
+use crate::signature::ConstructorSignatures;
+use crate::source::Location;
+
+/// A single finding, located in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub path: String,
+    pub location: Location,
+    pub message: String,
+    /// What created the named tuple a reported class inherits from.
+    pub factory: Option<Factory>,
+    /// What the reported class body does, see [`crate::intent`].
+    pub intents: Vec<Intent>,
+    /// For subclasses that declare fields, the constructor they actually get
+    /// and the one they look like they should get.
+    pub signatures: Option<ConstructorSignatures>,
+    /// Further explanation, printed below the message.
+    pub notes: Vec<String>,
+    /// How the reported code behaves depending on the Python version, if
+    /// that can be told statically.
+    pub behaviour: Option<Behaviour>,
+    /// The outcomes of [`Diagnostic::behaviour`] on the target versions.
+    pub runtime: Vec<VersionOutcome>,
+}
+
+impl Diagnostic {
+    /// A diagnostic without any of the optional details.
+    pub fn new(rule: Rule, path: impl Into<String>, location: Location, message: String) -> Self {
+        Self {
+            rule,
+            path: path.into(),
+            location,
+            message,
+            factory: None,
+            intents: Vec::new(),
+            signatures: None,
+            notes: Vec::new(),
+            behaviour: None,
+            runtime: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} {}",
+            self.path,
+            self.location,
+            self.rule.code(),
+            self.message
+        )?;
+        if !self.intents.is_empty() {
+            f.write_str(" [intent: ")?;
+            for (i, intent) in self.intents.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{intent}")?;
+            }
+            f.write_str("]")?;
+        }
+        if let Some(signatures) = &self.signatures {
+            write!(f, "\n{signatures}")?;
+        }
+        for outcome in &self.runtime {
+            write!(f, "\n  {outcome}")?;
+        }
+        for note in &self.notes {
+            write!(f, "\n  {note}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+//! Finding the Python files to check.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+
+/// Directory names that never contain first-party sources.
+const SKIPPED_DIRS: &[&str] = &["__pycache__", "node_modules", "site-packages"];
+
+/// Expands `paths` into a sorted list of Python files, stubs and archives.
+///
+/// Files given explicitly are kept regardless of their extension; directories
+/// are walked recursively, skipping hidden and cache directories.
+pub fn python_files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            walk(path, &mut files)?;
+        } else if path.exists() {
+            files.push(path.clone());
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{}: no such file or directory", path.display()),
+            ));
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if entry.file_type()?.is_dir() {
+            if !is_skipped_dir(&name) {
+                walk(&path, files)?;
+            }
+        } else if is_python_file(&path) || archive::is_archive(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Hidden and cache directories, which are never descended into.
+pub fn is_skipped_dir(name: &str) -> bool {
+    name.starts_with('.') || SKIPPED_DIRS.contains(&name)
+}
+
+/// Python sources and `.pyi` stubs.
+pub fn is_python_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "py" || ext == "pyi")
+}
+
+/// `path`, as diagnostics report it, relative to the directory `root` with
+/// `/` separators. Archive members keep their `!inner/path.py`. `None` if
+/// the file isn't under `root`.
+pub fn relative_path(root: &Path, path: &str) -> Option<String> {
+    let (on_disk, member) = match path.split_once('!') {
+        Some((on_disk, member)) => (on_disk, Some(member)),
+        None => (path, None),
+    };
+    let on_disk = Path::new(on_disk).canonicalize().ok()?;
+    let relative = on_disk.strip_prefix(root).ok()?;
+    let components: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    let mut relative = components.join("/");
+    if let Some(member) = member {
+        relative.push('!');
+        relative.push_str(member);
+    }
+    Some(relative)
+}
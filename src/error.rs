@@ -0,0 +1,58 @@
+use std::fmt;
+use std::io;
+
+use crate::source::Location;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// A file that is not valid Python.
+    Parse {
+        path: String,
+        location: Location,
+        message: String,
+    },
+    /// An invalid `pyproject.toml` or setting in it.
+    Config {
+        path: String,
+        message: String,
+    },
+    /// A baseline file that can't be read back.
+    Baseline {
+        path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Parse {
+                path,
+                location,
+                message,
+            } => write!(f, "{path}:{location}: syntax error: {message}"),
+            Error::Config { path, message } | Error::Baseline { path, message } => {
+                write!(f, "{path}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Parse { .. } | Error::Config { .. } | Error::Baseline { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,786 @@
+//! Migrating typed named tuple hierarchies to frozen dataclasses.
+//!
+//! ```python
+//! class Point2D(NamedTuple):
+//!     x: int
+//!     y: int
+//!
+//! class Point3D(Point2D):
+//!     z: int
+//! ```
+//!
+//! becomes
+//!
+//! ```python
+//! @dataclass(frozen=True)
+//! class Point2D:
+//!     x: int
+//!     y: int
+//!
+//! @dataclass(frozen=True)
+//! class Point3D(Point2D):
+//!     z: int
+//! ```
+//!
+//! so that `Point3D` really gets a `z` field. Only hierarchies, named tuples
+//! that have subclasses, are migrated, and all of their classes at once.
+//!
+//! The `NamedTuple` import goes too, unless the module still uses it.
+//!
+//! Dataclasses aren't tuples. Code indexing, unpacking, iterating or
+//! measuring instances, calling `_replace`, `_asdict` or `_make`, testing
+//! membership in them or comparing them with plain tuples would break, so
+//! hierarchies used like that are listed instead of rewritten. Instances are
+//! tracked through `self` in the hierarchy's methods, parameters annotated
+//! with one of its classes, and names assigned a constructor call.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use rustpython_parser::ast::{self, CmpOp, Expr, Operator, Ranged, Stmt};
+use rustpython_parser::text_size::TextRange;
+
+use crate::fix::{self, ClassFix, Edit, Outcome};
+use crate::module::ScopeId;
+use crate::project::{ClassId, ClassKind, Factory, ModuleId, Project, Value};
+use crate::signature::{self, Signature};
+use crate::visit::{self, Visitor};
+
+/// Attributes only named tuples have, or that mean something else on them.
+const TUPLE_ATTRIBUTES: &[&str] = &[
+    "_replace",
+    "_asdict",
+    "_make",
+    "_fields",
+    "_field_defaults",
+    "count",
+    "index",
+];
+
+/// Builtins that treat their argument as a sequence.
+const SEQUENCE_FUNCTIONS: &[&str] = &[
+    "tuple",
+    "list",
+    "set",
+    "frozenset",
+    "len",
+    "iter",
+    "reversed",
+    "sorted",
+    "enumerate",
+    "zip",
+    "sum",
+    "min",
+    "max",
+];
+
+/// Fixes for the classes of every typed named tuple hierarchy.
+pub fn fixes(project: &Project) -> Vec<ClassFix> {
+    let hierarchies: Vec<Vec<ClassId>> = project
+        .classes()
+        .filter(|(root, _)| project.class_kind(*root) == ClassKind::TypedNamedTuple)
+        .map(|(root, _)| hierarchy(project, root))
+        .filter(|members| members.len() >= 2)
+        .collect();
+    let mut roots: HashMap<ClassId, Vec<ClassId>> = HashMap::new();
+    for members in &hierarchies {
+        for member in members {
+            roots.entry(*member).or_default().push(members[0]);
+        }
+    }
+    let mut fixes = Vec::new();
+    for members in &hierarchies {
+        let root = members[0];
+        // Migrating a class once per hierarchy would merge their fields.
+        let shared = members.iter().find_map(|member| {
+            let other = roots[member].iter().find(|other| **other != root)?;
+            Some((*member, *other))
+        });
+        if let Some((member, other)) = shared {
+            let reason = format!(
+                "`{}` also inherits from `{}`, the root of another hierarchy",
+                project.class(member).qualname,
+                project.class(other).qualname
+            );
+            fixes.push(class_fix(project, root, Outcome::Skipped(reason)));
+            continue;
+        }
+        match migrate(project, members) {
+            Ok(edits) => {
+                for (member, edits) in members.iter().zip(edits) {
+                    fixes.push(class_fix(project, *member, Outcome::Fixed(edits)));
+                }
+            }
+            Err(reason) => fixes.push(class_fix(project, root, Outcome::Skipped(reason))),
+        }
+    }
+    fixes
+}
+
+fn class_fix(project: &Project, id: ClassId, outcome: Outcome) -> ClassFix {
+    let module = project.module(id.module);
+    let class = project.class(id);
+    ClassFix {
+        path: module.source.path().to_owned(),
+        location: module.source.location(class.node.start()),
+        qualname: class.qualname.clone(),
+        outcome,
+    }
+}
+
+/// `root` followed by its subclasses, parents before children.
+fn hierarchy(project: &Project, root: ClassId) -> Vec<ClassId> {
+    let mut members = vec![root];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (id, class) in project.classes() {
+            if members.contains(&id) {
+                continue;
+            }
+            let is_child = class.node.bases.iter().any(|base| {
+                matches!(project.resolve(id.module, class.scope, base), Value::Class(parent) if members.contains(&parent))
+            });
+            if is_child {
+                members.push(id);
+                changed = true;
+            }
+        }
+    }
+    members
+}
+
+/// The edits for each member, in the order of `members`.
+///
+/// Each module gets the `dataclass` import once per hierarchy; `fix::apply`
+/// makes the same import added by two hierarchies once.
+fn migrate(project: &Project, members: &[ClassId]) -> Result<Vec<Vec<Edit>>, String> {
+    let root = members[0];
+    check_members(project, members)?;
+    let usages = tuple_usages(project, members);
+    if !usages.is_empty() {
+        let mut reason = format!(
+            "instances are used as tuples in {} place{}:",
+            usages.len(),
+            if usages.len() == 1 { "" } else { "s" }
+        );
+        for usage in usages {
+            reason.push_str("\n  ");
+            reason.push_str(&usage);
+        }
+        return Err(reason);
+    }
+
+    let mut decorators = HashMap::new();
+    for member in members {
+        let module = project.module(member.module);
+        if decorators.contains_key(&member.module) {
+            continue;
+        }
+        let decorator = match fix::imported_name(module, "dataclasses", "dataclass") {
+            Some(name) => name,
+            None if module
+                .scope(ScopeId::MODULE)
+                .bindings("dataclass")
+                .is_empty() =>
+            {
+                "dataclass".to_owned()
+            }
+            None => {
+                return Err(format!(
+                    "`dataclass` is bound to something else in `{}`",
+                    module.name
+                ))
+            }
+        };
+        decorators.insert(member.module, decorator);
+    }
+
+    let mut imported = HashSet::new();
+    let mut all_edits = Vec::new();
+    for member in members {
+        let module = project.module(member.module);
+        let source = &module.source;
+        let class = project.class(*member);
+        let mut edits = Vec::new();
+        let decorator = &decorators[&member.module];
+        if decorator == "dataclass"
+            && fix::imported_name(module, "dataclasses", "dataclass").is_none()
+            && imported.insert(member.module)
+        {
+            edits.push(Edit::insert(
+                fix::import_offset(module),
+                "from dataclasses import dataclass\n",
+            ));
+        }
+        let start = fix::decorated_start(source, class.node.start(), &class.node.decorator_list);
+        let indent = fix::indentation(source, start).unwrap_or_default();
+        edits.push(Edit::insert(
+            start,
+            format!("@{decorator}(frozen=True)\n{indent}"),
+        ));
+        if *member == root {
+            edits.push(remove_namedtuple_base(project, root)?);
+            edits.extend(remove_namedtuple_import(project, root));
+        }
+        all_edits.push(edits);
+    }
+    Ok(all_edits)
+}
+
+fn check_members(project: &Project, members: &[ClassId]) -> Result<(), String> {
+    let mut signatures: HashMap<ClassId, Signature> = HashMap::new();
+    for member in members {
+        let module = project.module(member.module);
+        let class = project.class(*member);
+        if !fix::is_writable(&module.source) {
+            return Err(format!("`{}` is inside an archive", class.qualname));
+        }
+        if class
+            .node
+            .decorator_list
+            .iter()
+            .any(|decorator| module.source.slice(decorator.range()).contains("dataclass"))
+        {
+            return Err(format!("`{}` is already a dataclass", class.qualname));
+        }
+        if let Some(name) = ["__new__", "__init__", "__post_init__"]
+            .into_iter()
+            .find(|name| defines(&class.node.body, name))
+        {
+            return Err(format!("`{}` defines `{name}`", class.qualname));
+        }
+        let fields = signature::class_fields(&module.source, &class.node.body);
+        if let Some(field) = class.node.body.iter().find_map(mutable_default) {
+            return Err(format!(
+                "field `{field}` of `{}` has a mutable default",
+                class.qualname
+            ));
+        }
+        let parents: Vec<ClassId> = class
+            .node
+            .bases
+            .iter()
+            .filter_map(
+                |base| match project.resolve(member.module, class.scope, base) {
+                    Value::Class(parent) if members.contains(&parent) => Some(parent),
+                    _ => None,
+                },
+            )
+            .collect();
+        if parents.len() > 1 {
+            return Err(format!(
+                "`{}` inherits from the hierarchy more than once",
+                class.qualname
+            ));
+        }
+        let inherited = parents
+            .first()
+            .and_then(|parent| signatures.get(parent))
+            .cloned()
+            .unwrap_or_default();
+        let signature = inherited.extend(&fields);
+        if let Some(field) = signature.misordered_field() {
+            return Err(format!(
+                "dataclasses reject non-default field `{}` after a default in `{}`",
+                field.name, class.qualname
+            ));
+        }
+        signatures.insert(*member, signature);
+    }
+    Ok(())
+}
+
+/// Removes the `NamedTuple` base from the root's class statement, along
+/// with the parentheses if it's the only one.
+fn remove_namedtuple_base(project: &Project, root: ClassId) -> Result<Edit, String> {
+    let class = project.class(root);
+    let source = &project.module(root.module).source;
+    let text = source.text();
+    let bases = &class.node.bases;
+    let position = bases
+        .iter()
+        .position(|base| {
+            project.resolve(root.module, class.scope, base) == Value::Factory(Factory::Typing)
+        })
+        .ok_or_else(|| format!("`{}` doesn't list `NamedTuple` as a base", class.qualname))?;
+    let base = &bases[position];
+    let range = if bases.len() == 1 && class.node.keywords.is_empty() {
+        let open = text[..usize::from(base.start())]
+            .rfind('(')
+            .ok_or_else(|| format!("can't find the bases of `{}`", class.qualname))?;
+        let close = text[usize::from(base.end())..]
+            .find(')')
+            .map(|i| usize::from(base.end()) + i + 1)
+            .ok_or_else(|| format!("can't find the bases of `{}`", class.qualname))?;
+        TextRange::new(
+            open.try_into().unwrap_or_default(),
+            close.try_into().unwrap_or_default(),
+        )
+    } else if let Some(next) = bases.get(position + 1) {
+        TextRange::new(base.start(), next.start())
+    } else {
+        let previous = &bases[position - 1];
+        TextRange::new(previous.end(), base.end())
+    };
+    Ok(Edit::replace(range, ""))
+}
+
+/// Removes the import of the name the root's `NamedTuple` base refers to,
+/// if the module doesn't use or export it anywhere else. Only unconditional
+/// `from typing import ...` statements are touched.
+fn remove_namedtuple_import(project: &Project, root: ClassId) -> Option<Edit> {
+    let class = project.class(root);
+    let module = project.module(root.module);
+    let source = &module.source;
+    let name = class.node.bases.iter().find_map(|base| match base {
+        Expr::Name(ast::ExprName { id, .. })
+            if project.resolve(root.module, class.scope, base)
+                == Value::Factory(Factory::Typing) =>
+        {
+            Some(id.as_str())
+        }
+        _ => None,
+    })?;
+    let mut uses = NameUses { name, count: 0 };
+    uses.visit_body(&module.body);
+    let exported = module
+        .all
+        .as_ref()
+        .is_some_and(|all| all.iter().any(|exported| exported == name));
+    if uses.count > 1 || exported {
+        return None;
+    }
+    let (stmt, names) = module.body.iter().find_map(|stmt| match stmt {
+        Stmt::ImportFrom(ast::StmtImportFrom {
+            module: Some(from),
+            names,
+            ..
+        }) if matches!(from.as_str(), "typing" | "typing_extensions")
+            && names.iter().any(|alias| bound_name(alias) == name) =>
+        {
+            Some((stmt, names))
+        }
+        _ => None,
+    })?;
+    let position = names.iter().position(|alias| bound_name(alias) == name)?;
+    let range = if names.len() == 1 {
+        // The whole statement, if nothing else is on its lines.
+        let end = fix::next_line_start(source, stmt.end());
+        let rest = &source.text()[usize::from(stmt.end())..usize::from(end)];
+        if fix::indentation(source, stmt.start()) != Some("") || !rest.trim().is_empty() {
+            return None;
+        }
+        TextRange::new(stmt.start(), end)
+    } else if let Some(next) = names.get(position + 1) {
+        TextRange::new(names[position].start(), next.start())
+    } else {
+        TextRange::new(names[position - 1].end(), names[position].end())
+    };
+    Some(Edit::replace(range, ""))
+}
+
+fn bound_name(alias: &ast::Alias) -> &str {
+    alias.asname.as_ref().unwrap_or(&alias.name).as_str()
+}
+
+/// Counts the expressions reading or writing `name` in a module.
+struct NameUses<'n> {
+    name: &'n str,
+    count: usize,
+}
+
+impl<'a> Visitor<'a> for NameUses<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Name(ast::ExprName { id, .. }) = expr {
+            if id.as_str() == self.name {
+                self.count += 1;
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn defines(body: &[Stmt], name: &str) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Stmt::FunctionDef(ast::StmtFunctionDef { name: defined, .. })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name: defined, .. }) => {
+            defined.as_str() == name
+        }
+        _ => false,
+    })
+}
+
+/// The name of a field whose default is a list, dict or set, which
+/// dataclasses refuse.
+fn mutable_default(stmt: &Stmt) -> Option<&str> {
+    let Stmt::AnnAssign(ast::StmtAnnAssign {
+        target,
+        annotation,
+        value: Some(value),
+        ..
+    }) = stmt
+    else {
+        return None;
+    };
+    if signature::is_classvar(annotation) {
+        return None;
+    }
+    let Expr::Name(ast::ExprName { id, .. }) = target.as_ref() else {
+        return None;
+    };
+    let mutable = match value.as_ref() {
+        Expr::List(_)
+        | Expr::Dict(_)
+        | Expr::Set(_)
+        | Expr::ListComp(_)
+        | Expr::DictComp(_)
+        | Expr::SetComp(_) => true,
+        Expr::Call(ast::ExprCall { func, .. }) => matches!(
+            func.as_ref(),
+            Expr::Name(ast::ExprName { id, .. }) if matches!(id.as_str(), "list" | "dict" | "set")
+        ),
+        _ => false,
+    };
+    mutable.then_some(id.as_str())
+}
+
+/// Every place in the project that treats an instance of the hierarchy as a
+/// tuple, as `path:line:column: what`.
+fn tuple_usages(project: &Project, members: &[ClassId]) -> Vec<String> {
+    let mut usages = BTreeSet::new();
+    for (id, module) in project.modules() {
+        let mut finder = TupleUsageFinder {
+            project,
+            module: id,
+            members,
+            frames: vec![Frame::default()],
+            class_depth: Vec::new(),
+            usages: Vec::new(),
+        };
+        finder.visit_body(&module.body);
+        for (range, what) in finder.usages {
+            usages.insert((
+                module.source.path().to_owned(),
+                module.source.location(range.start()),
+                format!("{what} `{}`", module.source.slice(range)),
+            ));
+        }
+    }
+    usages
+        .into_iter()
+        .map(|(path, location, what)| format!("{path}:{location}: {what}"))
+        .collect()
+}
+
+/// The names known to hold instances or classes of the hierarchy in one
+/// function or at module level.
+#[derive(Default)]
+struct Frame {
+    instances: HashSet<String>,
+    classes: HashSet<String>,
+}
+
+struct TupleUsageFinder<'a> {
+    project: &'a Project,
+    module: ModuleId,
+    members: &'a [ClassId],
+    frames: Vec<Frame>,
+    /// For each enclosing class statement, whether it's in the hierarchy.
+    class_depth: Vec<bool>,
+    usages: Vec<(TextRange, &'static str)>,
+}
+
+impl TupleUsageFinder<'_> {
+    fn is_class(&self, expr: &Expr) -> bool {
+        if let Expr::Name(ast::ExprName { id, .. }) = expr {
+            if self
+                .frames
+                .iter()
+                .any(|frame| frame.classes.contains(id.as_str()))
+            {
+                return true;
+            }
+        }
+        matches!(expr, Expr::Name(_) | Expr::Attribute(_))
+            && matches!(
+                self.project.resolve(self.module, ScopeId::MODULE, expr),
+                Value::Class(id) if self.members.contains(&id)
+            )
+    }
+
+    fn is_instance(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Name(ast::ExprName { id, .. }) => self
+                .frames
+                .iter()
+                .any(|frame| frame.instances.contains(id.as_str())),
+            Expr::Call(ast::ExprCall { func, .. }) => match func.as_ref() {
+                Expr::Attribute(ast::ExprAttribute { value, attr, .. })
+                    if matches!(attr.as_str(), "_make" | "_replace") =>
+                {
+                    self.is_class(value) || self.is_instance(value)
+                }
+                func => self.is_class(func),
+            },
+            _ => false,
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames
+            .last_mut()
+            .expect("the module frame is never popped")
+    }
+
+    fn visit_function(&mut self, args: &ast::Arguments, decorator_list: &[Expr], body: &[Stmt]) {
+        let mut frame = Frame::default();
+        let parameters: Vec<_> = args
+            .posonlyargs
+            .iter()
+            .chain(&args.args)
+            .chain(&args.kwonlyargs)
+            .collect();
+        if self.class_depth.last() == Some(&true) {
+            let is_decorated = |name: &str| {
+                decorator_list.iter().any(|decorator| {
+                    matches!(decorator, Expr::Name(ast::ExprName { id, .. }) if id.as_str() == name)
+                })
+            };
+            if let Some(first) = parameters.first() {
+                let name = first.def.arg.to_string();
+                if is_decorated("classmethod") {
+                    frame.classes.insert(name);
+                } else if !is_decorated("staticmethod") {
+                    frame.instances.insert(name);
+                }
+            }
+        }
+        for parameter in &parameters {
+            if let Some(annotation) = &parameter.def.annotation {
+                if self.is_class(annotation) {
+                    frame.instances.insert(parameter.def.arg.to_string());
+                }
+            }
+        }
+        self.frames.push(frame);
+        self.class_depth.push(false);
+        self.visit_body(body);
+        self.class_depth.pop();
+        self.frames.pop();
+    }
+
+    fn check_unpacking(&mut self, target: &Expr, value: &Expr) {
+        if matches!(target, Expr::Tuple(_) | Expr::List(_)) && self.is_instance(value) {
+            self.usages.push((value.range(), "unpacking"));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for TupleUsageFinder<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                args,
+                decorator_list,
+                body,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                args,
+                decorator_list,
+                body,
+                ..
+            }) => {
+                for decorator in decorator_list {
+                    self.visit_expr(decorator);
+                }
+                visit::walk_arguments(self, args);
+                self.visit_function(args, decorator_list, body);
+            }
+            Stmt::ClassDef(ast::StmtClassDef {
+                name,
+                bases,
+                keywords,
+                decorator_list,
+                body,
+                ..
+            }) => {
+                for expr in decorator_list
+                    .iter()
+                    .chain(bases)
+                    .chain(keywords.iter().map(|keyword| &keyword.value))
+                {
+                    self.visit_expr(expr);
+                }
+                let in_hierarchy = self
+                    .project
+                    .module(self.module)
+                    .classes
+                    .iter()
+                    .enumerate()
+                    .any(|(index, class)| {
+                        class.node.range() == stmt.range()
+                            && class.name == name.as_str()
+                            && self.members.contains(&ClassId {
+                                module: self.module,
+                                index,
+                            })
+                    });
+                self.class_depth.push(in_hierarchy);
+                self.visit_body(body);
+                self.class_depth.pop();
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                for target in targets {
+                    self.check_unpacking(target, value);
+                    if let Expr::Name(ast::ExprName { id, .. }) = target {
+                        if self.is_instance(value) {
+                            self.frame().instances.insert(id.to_string());
+                        }
+                    }
+                }
+                visit::walk_stmt(self, stmt);
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                annotation,
+                value,
+                ..
+            }) => {
+                if let Expr::Name(ast::ExprName { id, .. }) = target.as_ref() {
+                    let is_instance = self.is_class(annotation)
+                        || value
+                            .as_deref()
+                            .is_some_and(|value| self.is_instance(value));
+                    if is_instance && self.class_depth.last() != Some(&true) {
+                        self.frame().instances.insert(id.to_string());
+                    }
+                }
+                visit::walk_stmt(self, stmt);
+            }
+            Stmt::For(ast::StmtFor { iter, .. })
+            | Stmt::AsyncFor(ast::StmtAsyncFor { iter, .. }) => {
+                if self.is_instance(iter) {
+                    self.usages.push((iter.range(), "iteration over"));
+                }
+                visit::walk_stmt(self, stmt);
+            }
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Subscript(ast::ExprSubscript { value, .. }) if self.is_instance(value) => {
+                self.usages.push((expr.range(), "indexing"));
+            }
+            Expr::Starred(ast::ExprStarred { value, .. }) if self.is_instance(value) => {
+                self.usages.push((expr.range(), "unpacking"));
+            }
+            Expr::Attribute(ast::ExprAttribute { value, attr, .. })
+                if TUPLE_ATTRIBUTES.contains(&attr.as_str())
+                    && (self.is_instance(value) || self.is_class(value)) =>
+            {
+                self.usages.push((expr.range(), "tuple attribute"));
+            }
+            Expr::Call(ast::ExprCall { func, args, .. }) => {
+                if let Expr::Name(ast::ExprName { id, .. }) = func.as_ref() {
+                    let sequence = SEQUENCE_FUNCTIONS.contains(&id.as_str())
+                        && args.iter().any(|arg| self.is_instance(arg));
+                    let isinstance = id.as_str() == "isinstance"
+                        && matches!(args.as_slice(), [value, Expr::Name(ast::ExprName { id: class, .. })]
+                            if class.as_str() == "tuple" && self.is_instance(value));
+                    if sequence {
+                        self.usages.push((expr.range(), "sequence function"));
+                    } else if isinstance {
+                        self.usages.push((expr.range(), "tuple check"));
+                    }
+                }
+            }
+            Expr::Compare(ast::ExprCompare {
+                left,
+                ops,
+                comparators,
+                ..
+            }) => {
+                let operands: Vec<&Expr> =
+                    std::iter::once(left.as_ref()).chain(comparators).collect();
+                for (op, pair) in ops.iter().zip(operands.windows(2)) {
+                    if matches!(op, CmpOp::In | CmpOp::NotIn) && self.is_instance(pair[1]) {
+                        self.usages.push((expr.range(), "membership test"));
+                        break;
+                    }
+                    let instance = pair.iter().any(|operand| self.is_instance(operand));
+                    let tuple = pair.iter().any(|operand| operand.is_tuple_expr());
+                    let ordering = matches!(op, CmpOp::Lt | CmpOp::LtE | CmpOp::Gt | CmpOp::GtE);
+                    if instance && (tuple || ordering) {
+                        self.usages.push((expr.range(), "tuple comparison"));
+                        break;
+                    }
+                }
+            }
+            Expr::BinOp(ast::ExprBinOp {
+                left, op, right, ..
+            }) if matches!(op, Operator::Add | Operator::Mult)
+                && (self.is_instance(left) || self.is_instance(right)) =>
+            {
+                self.usages.push((expr.range(), "tuple arithmetic"));
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::tests::project;
+
+    const HIERARCHY: &str = "from typing import NamedTuple, Optional\n\
+                             class Point(NamedTuple):\n\
+                             \x20   x: int\n\
+                             class Point3D(Point):\n\
+                             \x20   z: Optional[int] = None\n";
+
+    fn root(project: &Project) -> ClassId {
+        let (id, _) = project.classes().find(|(_, c)| c.name == "Point").unwrap();
+        id
+    }
+
+    #[test]
+    fn membership_tests_are_tuple_usages() {
+        let usages = |test: &str| {
+            let text = format!("{HIERARCHY}p = Point3D(1)\nfound = {test}\n");
+            let project = project(&[("m", false, &text)]);
+            tuple_usages(&project, &hierarchy(&project, root(&project)))
+        };
+        assert_eq!(usages("1 in p"), ["m.py:7:9: membership test `1 in p`"]);
+        assert_eq!(
+            usages("1 not in p"),
+            ["m.py:7:9: membership test `1 not in p`"]
+        );
+        assert!(usages("p in [p]").is_empty());
+    }
+
+    #[test]
+    fn unused_namedtuple_imports_are_removed() {
+        let removed = |text: &str| {
+            let project = project(&[("m", false, text)]);
+            remove_namedtuple_import(&project, root(&project))
+                .map(|edit| text[edit.range].to_owned())
+        };
+        assert_eq!(removed(HIERARCHY).as_deref(), Some("NamedTuple, "));
+        let alone = HIERARCHY
+            .replace(", Optional", "")
+            .replace("Optional[int]", "int");
+        assert_eq!(
+            removed(&alone).as_deref(),
+            Some("from typing import NamedTuple\n")
+        );
+        let used = format!("{HIERARCHY}Pair = NamedTuple('Pair', [('a', int)])\n");
+        assert_eq!(removed(&used), None);
+        let exported = format!("{HIERARCHY}__all__ = ['Point', 'NamedTuple']\n");
+        assert_eq!(removed(&exported), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Marking typed named tuples `@final`.
+//!
+//! Type checkers don't treat typed named tuples as implicitly final yet, but
+//! they do report subclasses of classes decorated with `typing.final`. This
+//! codemod adds the decorator to every `class X(NamedTuple)` that doesn't
+//! have it, importing `final` the way the module already imports from
+//! `typing` or `typing_extensions`.
+
+use rustpython_parser::ast::{self, Expr, Ranged, Stmt};
+
+use crate::fix::{self, ClassFix, Edit, Outcome};
+use crate::module::{Binding, Module, ScopeId};
+use crate::project::{ClassKind, Project};
+
+/// Fixes adding `@final` to every typed named tuple class without it.
+pub fn fixes(project: &Project) -> Vec<ClassFix> {
+    let mut fixes = Vec::new();
+    for (id, class) in project.classes() {
+        if project.class_kind(id) != ClassKind::TypedNamedTuple
+            || class.node.decorator_list.iter().any(is_final)
+        {
+            continue;
+        }
+        let module = project.module(id.module);
+        let source = &module.source;
+        let outcome = if fix::is_writable(source) {
+            match final_reference(module, &class.node.bases) {
+                Ok((name, import)) => {
+                    let start = fix::decorated_start(
+                        source,
+                        class.node.start(),
+                        &class.node.decorator_list,
+                    );
+                    let indent = fix::indentation(source, start).unwrap_or_default();
+                    let mut edits: Vec<Edit> = import.into_iter().collect();
+                    edits.push(Edit::insert(start, format!("@{name}\n{indent}")));
+                    Outcome::Fixed(edits)
+                }
+                Err(reason) => Outcome::Skipped(reason),
+            }
+        } else {
+            Outcome::Skipped("the file is inside an archive".to_owned())
+        };
+        fixes.push(ClassFix {
+            path: source.path().to_owned(),
+            location: source.location(class.node.start()),
+            qualname: class.qualname.clone(),
+            outcome,
+        });
+    }
+    fixes
+}
+
+/// `@final`, `@typing.final`, `@t.final` and the like.
+fn is_final(decorator: &Expr) -> bool {
+    match decorator {
+        Expr::Name(ast::ExprName { id, .. }) => id.as_str() == "final",
+        Expr::Attribute(ast::ExprAttribute { attr, .. }) => attr.as_str() == "final",
+        _ => false,
+    }
+}
+
+/// How to refer to `final` in `module`, and the edit importing it if needed.
+fn final_reference(module: &Module, bases: &[Expr]) -> Result<(String, Option<Edit>), String> {
+    let preferred = bases
+        .iter()
+        .find_map(|base| typing_module(module, base))
+        .unwrap_or("typing");
+    let other = match preferred {
+        "typing" => "typing_extensions",
+        _ => "typing",
+    };
+    for from in [preferred, other] {
+        if let Some(name) = fix::imported_name(module, from, "final") {
+            return Ok((name, None));
+        }
+    }
+    if !module.scope(ScopeId::MODULE).bindings("final").is_empty() {
+        return Err("`final` is bound to something else in this module".to_owned());
+    }
+    let existing = module.body.iter().find_map(|stmt| match stmt {
+        Stmt::ImportFrom(ast::StmtImportFrom {
+            module: Some(from),
+            names,
+            level,
+            ..
+        }) if from.as_str() == preferred
+            && level.map_or(0, |level| level.to_u32()) == 0
+            && names.iter().all(|alias| alias.name.as_str() != "*") =>
+        {
+            names.last()
+        }
+        _ => None,
+    });
+    let import = match existing {
+        Some(alias) => Edit::insert(alias.end(), ", final"),
+        None => Edit::insert(
+            fix::import_offset(module),
+            format!("from {preferred} import final\n"),
+        ),
+    };
+    Ok(("final".to_owned(), Some(import)))
+}
+
+/// `typing` or `typing_extensions`, whichever the `NamedTuple` in `base`
+/// was imported from.
+fn typing_module<'a>(module: &'a Module, base: &Expr) -> Option<&'a str> {
+    let scope = module.scope(ScopeId::MODULE);
+    let from = match base {
+        Expr::Name(ast::ExprName { id, .. }) => {
+            scope.bindings(id).iter().find_map(|binding| match binding {
+                Binding::ImportFrom { module, .. } => Some(module.as_str()),
+                _ => None,
+            })
+        }
+        Expr::Attribute(ast::ExprAttribute { value, .. }) => match value.as_ref() {
+            Expr::Name(ast::ExprName { id, .. }) => {
+                scope.bindings(id).iter().find_map(|binding| match binding {
+                    Binding::Module(module) => Some(module.as_str()),
+                    _ => None,
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    }?;
+    matches!(from, "typing" | "typing_extensions").then_some(from)
+}
@@ -0,0 +1,361 @@
+//! Rewriting reported classes in place.
+//!
+//! A fixer looks at the whole project and produces one [`ClassFix`] per class
+//! it would rewrite, holding either the text edits or the reason the rewrite
+//! isn't safe. Edits of all fixes are applied per file afterwards.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use rustpython_parser::ast::{self, Ranged, Stmt};
+use rustpython_parser::text_size::{TextRange, TextSize};
+use similar::TextDiff;
+
+use crate::module::{Binding, Module, ScopeId};
+use crate::project::Project;
+use crate::source::{Location, SourceFile};
+
+pub mod dataclass;
+pub mod final_decorator;
+pub mod standalone;
+
+/// Which rewrite `--fix` applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Turn field-adding subclasses into standalone typed named tuples.
+    #[default]
+    Standalone,
+    /// Turn whole named tuple hierarchies into frozen dataclasses.
+    Dataclass,
+}
+
+impl Strategy {
+    pub const ALL: &'static [Strategy] = &[Strategy::Standalone, Strategy::Dataclass];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Strategy::Standalone => "standalone",
+            Strategy::Dataclass => "dataclass",
+        }
+    }
+
+    pub fn fixes(self, project: &Project) -> Vec<ClassFix> {
+        match self {
+            Strategy::Standalone => standalone::fixes(project),
+            Strategy::Dataclass => dataclass::fixes(project),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL
+            .iter()
+            .copied()
+            .find(|strategy| strategy.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Strategy::ALL.iter().map(|s| s.as_str()).collect();
+                format!(
+                    "unknown fix strategy `{s}`, expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Replaces `range` with `content`; an empty range is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: TextRange,
+    pub content: String,
+}
+
+impl Edit {
+    pub fn replace(range: TextRange, content: impl Into<String>) -> Self {
+        Self {
+            range,
+            content: content.into(),
+        }
+    }
+
+    pub fn insert(offset: TextSize, content: impl Into<String>) -> Self {
+        Self::replace(TextRange::empty(offset), content)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Fixed(Vec<Edit>),
+    /// Why rewriting the class automatically could change what it means.
+    Skipped(String),
+}
+
+/// The rewrite of one class.
+#[derive(Debug, Clone)]
+pub struct ClassFix {
+    pub path: String,
+    pub location: Location,
+    pub qualname: String,
+    pub outcome: Outcome,
+}
+
+/// The new contents of every file that `fixes` change, by path.
+///
+/// Identical edits, like the same import added for two classes, are made
+/// once. If a fix's edits overlap another's, nothing is written, because
+/// fixes can depend on each other, like the classes of one dataclass
+/// hierarchy; the indices of the fixes that overlap an earlier one are
+/// returned instead.
+pub fn apply<'a>(
+    sources: impl IntoIterator<Item = &'a SourceFile>,
+    fixes: &[ClassFix],
+) -> Result<BTreeMap<String, String>, Vec<usize>> {
+    let mut by_path: BTreeMap<&str, Vec<(usize, &[Edit])>> = BTreeMap::new();
+    for (index, fix) in fixes.iter().enumerate() {
+        if let Outcome::Fixed(edits) = &fix.outcome {
+            by_path.entry(&fix.path).or_default().push((index, edits));
+        }
+    }
+    let mut files = BTreeMap::new();
+    let mut overlapping = Vec::new();
+    for source in sources {
+        let Some(groups) = by_path.get(source.path()) else {
+            continue;
+        };
+        let mut accepted: Vec<&Edit> = Vec::new();
+        for (index, edits) in groups {
+            let edits: Vec<&Edit> = edits
+                .iter()
+                .filter(|edit| !accepted.contains(edit))
+                .collect();
+            let overlaps = edits.iter().any(|edit| {
+                accepted
+                    .iter()
+                    .any(|other| overlap(edit.range, other.range))
+            });
+            if overlaps {
+                overlapping.push(*index);
+            } else {
+                accepted.extend(edits);
+            }
+        }
+        accepted.sort_by_key(|edit| (edit.range.start(), edit.range.end()));
+        let text = source.text();
+        let mut output = String::with_capacity(text.len());
+        let mut last = 0;
+        for edit in accepted {
+            output.push_str(&text[last..usize::from(edit.range.start())]);
+            output.push_str(&edit.content);
+            last = usize::from(edit.range.end());
+        }
+        output.push_str(&text[last..]);
+        files.insert(source.path().to_owned(), output);
+    }
+    if overlapping.is_empty() {
+        Ok(files)
+    } else {
+        overlapping.sort_unstable();
+        Err(overlapping)
+    }
+}
+
+/// A unified diff from `old` to `new`, or an empty string if they're equal.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(path, path)
+        .to_string()
+}
+
+/// Whether two edits touch the same text. Insertions at the same offset
+/// overlap too, since their order would be arbitrary.
+fn overlap(a: TextRange, b: TextRange) -> bool {
+    a.start() < b.end() && b.start() < a.end() || a.start() == b.start()
+}
+
+/// Whether a source can be written back, as opposed to living in an archive.
+pub fn is_writable(source: &SourceFile) -> bool {
+    Path::new(source.path()).is_file()
+}
+
+/// The whitespace before `offset` on its line, or `None` if there is other
+/// text before it.
+pub fn indentation(source: &SourceFile, offset: TextSize) -> Option<&str> {
+    let text = source.text();
+    let offset = usize::from(offset);
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &text[line_start..offset];
+    indent
+        .chars()
+        .all(|c| c == ' ' || c == '\t')
+        .then_some(indent)
+}
+
+/// How `module` refers to `member` of module `from` at its top level: by the
+/// name `from from import member` binds, or as `alias.member` after
+/// `import from as alias`.
+pub fn imported_name(module: &Module, from: &str, member: &str) -> Option<String> {
+    let scope = module.scope(ScopeId::MODULE);
+    let mut direct = Vec::new();
+    let mut qualified = Vec::new();
+    for (name, binding) in scope.all_bindings() {
+        match binding {
+            Binding::ImportFrom {
+                module,
+                name: imported,
+            } if module == from && imported == member => {
+                direct.push(name.to_owned());
+            }
+            Binding::Module(module) if module == from => {
+                qualified.push(format!("{name}.{member}"));
+            }
+            _ => {}
+        }
+    }
+    direct.sort();
+    qualified.sort();
+    direct.into_iter().chain(qualified).next()
+}
+
+/// Where a new top-level import goes: after the imports at the top of the
+/// module, or else after its docstring and leading comments.
+pub fn import_offset(module: &Module) -> TextSize {
+    let source = &module.source;
+    let mut offset = TextSize::default();
+    for (i, stmt) in module.body.iter().enumerate() {
+        let is_docstring = i == 0
+            && matches!(stmt, Stmt::Expr(ast::StmtExpr { value, .. }) if value.is_constant_expr());
+        if !is_docstring && !matches!(stmt, Stmt::Import(_) | Stmt::ImportFrom(_)) {
+            break;
+        }
+        offset = next_line_start(source, stmt.end());
+    }
+    if offset == TextSize::default() {
+        let text = source.text();
+        while text[usize::from(offset)..].starts_with('#') {
+            offset = next_line_start(source, offset);
+        }
+    }
+    offset
+}
+
+/// The range of `stmt` including its decorators, which the parser leaves out.
+pub fn statement_range(source: &SourceFile, stmt: &Stmt) -> TextRange {
+    let decorators = match stmt {
+        Stmt::FunctionDef(ast::StmtFunctionDef { decorator_list, .. })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { decorator_list, .. })
+        | Stmt::ClassDef(ast::StmtClassDef { decorator_list, .. }) => decorator_list.as_slice(),
+        _ => &[],
+    };
+    TextRange::new(
+        decorated_start(source, stmt.start(), decorators),
+        stmt.end(),
+    )
+}
+
+/// Where a definition starting at `start` really starts: at the `@` of its
+/// first decorator, if it has any.
+pub fn decorated_start(source: &SourceFile, start: TextSize, decorators: &[ast::Expr]) -> TextSize {
+    decorators
+        .first()
+        .and_then(|decorator| {
+            let before = &source.text()[..usize::from(decorator.start())];
+            TextSize::try_from(before.rfind('@')?).ok()
+        })
+        .unwrap_or(start)
+}
+
+/// The offset just past the end of the line `offset` is on.
+pub fn next_line_start(source: &SourceFile, offset: TextSize) -> TextSize {
+    let text = source.text();
+    let offset = usize::from(offset);
+    let end = text[offset..]
+        .find('\n')
+        .map_or(text.len(), |i| offset + i + 1);
+    TextSize::try_from(end).unwrap_or_default()
+}
+
+/// The source of `range`, with continuation lines moved from indentation
+/// `from` to `to`. The first line is returned without indentation.
+pub fn reindent(source: &SourceFile, range: TextRange, from: &str, to: &str) -> String {
+    let mut output = String::new();
+    for (i, line) in source.slice(range).split('\n').enumerate() {
+        if i > 0 {
+            output.push('\n');
+            match line.strip_prefix(from) {
+                Some(rest) => {
+                    output.push_str(to);
+                    output.push_str(rest);
+                }
+                None => output.push_str(line),
+            }
+        } else {
+            output.push_str(line);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(edits: Vec<Edit>) -> ClassFix {
+        ClassFix {
+            path: "m.py".to_owned(),
+            location: Location { line: 1, column: 1 },
+            qualname: "A".to_owned(),
+            outcome: Outcome::Fixed(edits),
+        }
+    }
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(TextSize::new(start), TextSize::new(end))
+    }
+
+    #[test]
+    fn shared_edits_are_made_once() {
+        let source = SourceFile::new("m.py", "class A(B): pass\nclass C(B): pass\n");
+        let import = Edit::insert(TextSize::new(0), "import x\n");
+        let fixes = [
+            fix(vec![import.clone(), Edit::replace(range(8, 9), "X")]),
+            fix(vec![import, Edit::replace(range(25, 26), "X")]),
+        ];
+        let files = apply([&source], &fixes).unwrap();
+        assert_eq!(
+            files["m.py"],
+            "import x\nclass A(X): pass\nclass C(X): pass\n"
+        );
+    }
+
+    #[test]
+    fn overlapping_fixes_write_nothing() {
+        let source = SourceFile::new("m.py", "class A(B): pass\n");
+        let fixes = [
+            fix(vec![Edit::replace(range(8, 9), "X")]),
+            fix(vec![Edit::replace(range(6, 10), "A(Y)")]),
+            fix(vec![Edit::insert(TextSize::new(8), "Z")]),
+        ];
+        assert_eq!(apply([&source], &fixes), Err(vec![1, 2]));
+    }
+
+    #[test]
+    fn indentation_and_reindent() {
+        let source = SourceFile::new("m.py", "class A:\n    x = (\n        1)\n");
+        let start = TextSize::new(13);
+        assert_eq!(indentation(&source, start), Some("    "));
+        assert_eq!(indentation(&source, TextSize::new(15)), None);
+        let range = TextRange::new(start, TextSize::new(source.text().len() as u32 - 1));
+        assert_eq!(reindent(&source, range, "    ", ""), "x = (\n    1)");
+    }
+}
@@ -0,0 +1,301 @@
+//! Turning a field-adding subclass of a typed named tuple into a named tuple
+//! of its own.
+//!
+//! ```python
+//! class Point3D(Point2D):
+//!     z: int
+//! ```
+//!
+//! becomes
+//!
+//! ```python
+//! class Point3D(NamedTuple):
+//!     x: int
+//!     y: int
+//!     z: int
+//! ```
+//!
+//! The parent's fields are copied in order in front of the subclass's own,
+//! and the parent's methods and other attributes are appended after its body.
+//! Call sites are left alone. Classes whose rewrite would change behaviour,
+//! because of field defaults, overridden members, `ClassVar` annotations or
+//! zero-argument `super()`, are reported instead of rewritten.
+
+use std::collections::HashSet;
+
+use rustpython_parser::ast::{self, Expr, Ranged, Stmt};
+
+use crate::fix::{self, ClassFix, Edit, Outcome};
+use crate::module::Binding;
+use crate::project::{ClassId, ClassKind, Factory, Project, Value};
+use crate::rules;
+use crate::signature::{self, Field, Signature};
+
+/// Fixes for every field-adding subclass of a typed named tuple.
+pub fn fixes(project: &Project) -> Vec<ClassFix> {
+    let mut fixes = Vec::new();
+    for (id, class) in project.classes() {
+        let module = project.module(id.module);
+        let added = signature::class_fields(&module.source, &class.node.body);
+        if added.is_empty() {
+            continue;
+        }
+        for base in &class.node.bases {
+            let parent = match project.resolve(id.module, class.scope, base) {
+                Value::Class(base_id)
+                    if project.class_kind(base_id) == ClassKind::TypedNamedTuple =>
+                {
+                    Parent::Class(base_id)
+                }
+                Value::Functional(functional) if functional.factory == Factory::Typing => {
+                    Parent::Functional {
+                        name: functional.typename,
+                        fields: functional.fields,
+                    }
+                }
+                _ => continue,
+            };
+            fixes.push(ClassFix {
+                path: module.source.path().to_owned(),
+                location: module.source.location(class.node.start()),
+                qualname: class.qualname.clone(),
+                outcome: match rewrite(project, id, base, &parent, &added) {
+                    Ok(edits) => Outcome::Fixed(edits),
+                    Err(reason) => Outcome::Skipped(reason),
+                },
+            });
+        }
+    }
+    fixes
+}
+
+enum Parent {
+    Class(ClassId),
+    Functional {
+        name: String,
+        fields: Option<Vec<Field>>,
+    },
+}
+
+fn rewrite(
+    project: &Project,
+    id: ClassId,
+    base: &Expr,
+    parent: &Parent,
+    added: &[Field],
+) -> Result<Vec<Edit>, String> {
+    let class = project.class(id);
+    let module = project.module(id.module);
+    let source = &module.source;
+    if !fix::is_writable(source) {
+        return Err("the file is inside an archive".to_owned());
+    }
+    if class.node.bases.len() != 1 || !class.node.keywords.is_empty() {
+        return Err("it has other bases or class keywords".to_owned());
+    }
+    if matches!(base, Expr::Subscript(_)) {
+        return Err("it inherits through a generic alias".to_owned());
+    }
+    if let Some(name) = class.node.body.iter().find_map(classvar) {
+        return Err(format!(
+            "it declares class variable `{name}`, which a NamedTuple body rejects"
+        ));
+    }
+
+    let (parent_name, parent_fields, parent_body, factory_expr) = match parent {
+        Parent::Class(parent_id) => {
+            let parent_class = project.class(*parent_id);
+            if parent_id.module != id.module {
+                return Err(format!(
+                    "`{}` is defined in another module",
+                    parent_class.qualname
+                ));
+            }
+            let [factory_expr] = parent_class.node.bases.as_slice() else {
+                return Err(format!(
+                    "`{}` has more than one base",
+                    parent_class.qualname
+                ));
+            };
+            if !parent_class.node.type_params.is_empty() {
+                return Err(format!("`{}` is generic", parent_class.qualname));
+            }
+            if !matches!(factory_expr, Expr::Name(_) | Expr::Attribute(_)) {
+                return Err(format!("`{}` is generic", parent_class.qualname));
+            }
+            (
+                parent_class.qualname.as_str(),
+                signature::class_fields(source, &parent_class.node.body),
+                parent_class.node.body.as_slice(),
+                factory_expr,
+            )
+        }
+        Parent::Functional { name, fields } => {
+            let Some(fields) = fields else {
+                return Err(format!("the fields of `{name}` aren't literals"));
+            };
+            let Some(factory_expr) = functional_factory(project, id, base) else {
+                return Err(format!("can't tell where `{name}` is created"));
+            };
+            (name.as_str(), fields.clone(), &[][..], factory_expr)
+        }
+    };
+    if project.resolve(id.module, class.scope, factory_expr) != Value::Factory(Factory::Typing) {
+        return Err(format!(
+            "`{}` doesn't refer to `typing.NamedTuple` where `{}` is defined",
+            source.slice(factory_expr.range()),
+            class.qualname
+        ));
+    }
+
+    if let Some(field) = added
+        .iter()
+        .find(|field| parent_fields.iter().any(|p| p.name == field.name))
+    {
+        return Err(format!(
+            "it redeclares field `{}` of `{parent_name}`",
+            field.name
+        ));
+    }
+    let combined = Signature {
+        fields: parent_fields.clone(),
+    }
+    .extend(added);
+    if let Some(field) = combined.misordered_field() {
+        return Err(format!(
+            "field `{}` has no default but would follow a field with one",
+            field.name
+        ));
+    }
+    let parent_names: HashSet<&str> = defined_names(parent_body)
+        .chain(parent_fields.iter().map(|field| field.name.as_str()))
+        .collect();
+    if let Some(name) = defined_names(&class.node.body)
+        .chain(added.iter().map(|field| field.name.as_str()))
+        .find(|name| parent_names.contains(name))
+    {
+        return Err(format!("it overrides `{name}` from `{parent_name}`"));
+    }
+    if !rules::class_cell_usages(&class.node.body).is_empty()
+        || !rules::class_cell_usages(parent_body).is_empty()
+    {
+        return Err(
+            "it uses zero-argument `super()` or `__class__`, which fails in a NamedTuple body"
+                .to_owned(),
+        );
+    }
+
+    let body = &class.node.body;
+    let first = usize::from(is_docstring(&body[0]));
+    let first_start = fix::statement_range(source, &body[first]).start();
+    let Some(indent) = fix::indentation(source, first_start) else {
+        return Err("its body doesn't start on a line of its own".to_owned());
+    };
+
+    let mut edits = vec![Edit::replace(
+        base.range(),
+        source.slice(factory_expr.range()),
+    )];
+    if !parent_fields.is_empty() {
+        let mut content = String::new();
+        for field in &parent_fields {
+            content.push_str(&format!("{field}\n{indent}"));
+        }
+        edits.push(Edit::insert(first_start, content));
+    }
+    let copied: Vec<&Stmt> = parent_body
+        .iter()
+        .enumerate()
+        .filter(|&(i, stmt)| {
+            !(i == 0 && is_docstring(stmt) || is_field(stmt) || matches!(stmt, Stmt::Pass(_)))
+        })
+        .map(|(_, stmt)| stmt)
+        .collect();
+    if !copied.is_empty() {
+        let Some(from) = fix::indentation(source, fix::statement_range(source, copied[0]).start())
+        else {
+            return Err(format!(
+                "the body of `{parent_name}` doesn't start on a line of its own"
+            ));
+        };
+        let last = body.last().map_or(class.node.end(), Ranged::end);
+        let at = fix::next_line_start(source, last);
+        let mut content = String::new();
+        if usize::from(at) == source.text().len() && !source.text().ends_with('\n') {
+            content.push('\n');
+        }
+        for stmt in copied {
+            content.push('\n');
+            content.push_str(indent);
+            let range = fix::statement_range(source, stmt);
+            content.push_str(&fix::reindent(source, range, from, indent));
+            content.push('\n');
+        }
+        edits.push(Edit::insert(at, content));
+    }
+    Ok(edits)
+}
+
+/// For a class inheriting from a functional named tuple, the factory in the
+/// call creating it: the base itself or the value assigned to the base's name.
+fn functional_factory<'a>(project: &'a Project, id: ClassId, base: &'a Expr) -> Option<&'a Expr> {
+    match base {
+        Expr::Call(ast::ExprCall { func, .. }) => Some(func),
+        Expr::Name(ast::ExprName { id: name, .. }) => {
+            let class = project.class(id);
+            let (_, bindings) = project.module(id.module).lookup(class.scope, name)?;
+            bindings.iter().find_map(|binding| match binding {
+                Binding::Assign(value) => match value.as_ref() {
+                    Expr::Call(ast::ExprCall { func, .. }) => Some(func.as_ref()),
+                    _ => None,
+                },
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The names a class body binds directly, fields excluded.
+fn defined_names(body: &[Stmt]) -> impl Iterator<Item = &str> {
+    body.iter().flat_map(|stmt| -> Vec<&str> {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef { name, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, .. })
+            | Stmt::ClassDef(ast::StmtClassDef { name, .. }) => vec![name.as_str()],
+            Stmt::Assign(ast::StmtAssign { targets, .. }) => targets
+                .iter()
+                .filter_map(|target| match target {
+                    Expr::Name(ast::ExprName { id, .. }) => Some(id.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    })
+}
+
+/// The name a `ClassVar` annotation declares.
+fn classvar(stmt: &Stmt) -> Option<&str> {
+    match stmt {
+        Stmt::AnnAssign(ast::StmtAnnAssign {
+            target, annotation, ..
+        }) if signature::is_classvar(annotation) => match target.as_ref() {
+            Expr::Name(ast::ExprName { id, .. }) => Some(id.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_field(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::AnnAssign(ast::StmtAnnAssign { target, .. }) if target.is_name_expr())
+}
+
+fn is_docstring(stmt: &Stmt) -> bool {
+    matches!(
+        stmt,
+        Stmt::Expr(ast::StmtExpr { value, .. })
+            if matches!(value.as_ref(), Expr::Constant(ast::ExprConstant { value: ast::Constant::Str(_), .. }))
+    )
+}
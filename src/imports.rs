@@ -0,0 +1,149 @@
+//! Mapping files to dotted module names and resolving relative imports.
+
+use std::path::Path;
+
+/// The dotted name a file is importable under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModulePath {
+    pub name: String,
+    /// Whether the file is a package's `__init__.py` or `__init__.pyi`.
+    pub is_package: bool,
+}
+
+impl ModulePath {
+    /// Derives the module name of `path` from the chain of packages around it.
+    ///
+    /// Every enclosing directory that has an `__init__.py` or `__init__.pyi`
+    /// contributes a component; the first one that doesn't is taken as the
+    /// import root. In a typeshed checkout that's `stdlib/` or
+    /// `stubs/<distribution>/`.
+    ///
+    /// The path is made absolute first, so that the name doesn't depend on
+    /// the directory the tool runs in.
+    pub fn for_file(path: &Path) -> Self {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        Self::with_packages(&path, |dir| {
+            dir.join("__init__.py").is_file() || dir.join("__init__.pyi").is_file()
+        })
+    }
+
+    /// Like [`ModulePath::for_file`], with `has_init` telling which
+    /// directories are packages.
+    pub fn with_packages(path: &Path, has_init: impl Fn(&Path) -> bool) -> Self {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let is_package = stem == "__init__";
+        let mut components = Vec::new();
+        if !is_package {
+            components.push(stem);
+        }
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if !has_init(current) {
+                break;
+            }
+            match current.file_name() {
+                // PEP 561 stub-only packages are installed as `<package>-stubs`.
+                Some(name) => {
+                    let name = name.to_string_lossy();
+                    let name = name.strip_suffix("-stubs").unwrap_or(&name);
+                    components.push(name.to_owned());
+                }
+                None => break,
+            }
+            dir = current.parent();
+        }
+        components.reverse();
+        Self {
+            name: components.join("."),
+            is_package,
+        }
+    }
+}
+
+/// Resolves the module named by `from <dots><module> import ...` written in
+/// `importer`, or `None` if the dots go above the top-level package.
+pub fn resolve_relative(importer: &ModulePath, level: u32, module: Option<&str>) -> Option<String> {
+    if level == 0 {
+        return module.map(str::to_owned);
+    }
+    let mut parts: Vec<&str> = importer.name.split('.').filter(|p| !p.is_empty()).collect();
+    // A package's `__init__` is its own anchor for a single dot, a plain
+    // module is anchored at its parent package.
+    let strip = if importer.is_package {
+        level - 1
+    } else {
+        level
+    };
+    for _ in 0..strip {
+        parts.pop()?;
+    }
+    if let Some(module) = module {
+        parts.push(module);
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_path(path: &str, packages: &[&str]) -> ModulePath {
+        ModulePath::with_packages(Path::new(path), |dir| {
+            packages.iter().any(|package| Path::new(package) == dir)
+        })
+    }
+
+    #[test]
+    fn names_from_packages() {
+        let packages = ["src/pkg", "src/pkg/sub"];
+        let module = module_path("src/pkg/sub/models.py", &packages);
+        assert_eq!(module.name, "pkg.sub.models");
+        assert!(!module.is_package);
+        let package = module_path("src/pkg/sub/__init__.pyi", &packages);
+        assert_eq!(package.name, "pkg.sub");
+        assert!(package.is_package);
+        assert_eq!(module_path("src/script.py", &packages).name, "script");
+    }
+
+    #[test]
+    fn stub_only_packages() {
+        let module = module_path("site/pkg-stubs/models.pyi", &["site/pkg-stubs"]);
+        assert_eq!(module.name, "pkg.models");
+    }
+
+    #[test]
+    fn relative_imports() {
+        let module = ModulePath {
+            name: "pkg.sub.models".to_owned(),
+            is_package: false,
+        };
+        let package = ModulePath {
+            name: "pkg.sub".to_owned(),
+            is_package: true,
+        };
+        assert_eq!(
+            resolve_relative(&module, 1, Some("base")).as_deref(),
+            Some("pkg.sub.base")
+        );
+        assert_eq!(resolve_relative(&module, 2, None).as_deref(), Some("pkg"));
+        assert_eq!(
+            resolve_relative(&package, 1, Some("base")).as_deref(),
+            Some("pkg.sub.base")
+        );
+        assert_eq!(
+            resolve_relative(&package, 2, Some("base")).as_deref(),
+            Some("pkg.base")
+        );
+        assert_eq!(resolve_relative(&module, 3, None), None);
+        assert_eq!(
+            resolve_relative(&module, 0, Some("typing")).as_deref(),
+            Some("typing")
+        );
+    }
+}
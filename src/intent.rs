@@ -0,0 +1,188 @@
+//! Classifying what a named tuple subclass adds to its base.
+//!
+//! The tags are collected per class body so that findings across codebases
+//! can be grouped into a taxonomy of use cases. A class gets one tag per kind
+//! of member it defines, so "methods only" is a class tagged just
+//! [`Intent::Methods`].
+
+use std::fmt;
+
+use rustpython_parser::ast::{self, Expr, Stmt};
+
+use crate::signature;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Intent {
+    /// Nothing but `pass` or `...`.
+    Empty,
+    /// A docstring and nothing else.
+    DocstringOnly,
+    /// Regular, static or class methods.
+    Methods,
+    /// Properties, including `cached_property` and setters.
+    Properties,
+    /// Annotated names, which look like new fields but never become ones:
+    /// the constructor and `_fields` are inherited unchanged.
+    IgnoredFields,
+    /// Dunder methods such as `__new__` or `__repr__` replacing the generated ones.
+    DunderOverrides,
+    /// Plain class-level assignments and `ClassVar` annotations.
+    ClassConstants,
+    /// Anything else, e.g. nested classes or statements with side effects.
+    Other,
+}
+
+impl Intent {
+    pub const ALL: &'static [Intent] = &[
+        Intent::Empty,
+        Intent::DocstringOnly,
+        Intent::Methods,
+        Intent::Properties,
+        Intent::IgnoredFields,
+        Intent::DunderOverrides,
+        Intent::ClassConstants,
+        Intent::Other,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Intent::Empty => "empty",
+            Intent::DocstringOnly => "docstring-only",
+            Intent::Methods => "methods",
+            Intent::Properties => "properties",
+            Intent::IgnoredFields => "ignored-fields",
+            Intent::DunderOverrides => "dunder-overrides",
+            Intent::ClassConstants => "class-constants",
+            Intent::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for Intent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Tags a class body, returning sorted, deduplicated intents.
+pub fn classify(body: &[Stmt]) -> Vec<Intent> {
+    let mut intents = Vec::new();
+    let mut has_docstring = false;
+    for (index, stmt) in body.iter().enumerate() {
+        let intent = match stmt {
+            Stmt::Pass(_) => continue,
+            Stmt::Expr(ast::StmtExpr { value, .. }) => match value.as_ref() {
+                Expr::Constant(ast::ExprConstant {
+                    value: ast::Constant::Str(_),
+                    ..
+                }) if index == 0 => {
+                    has_docstring = true;
+                    continue;
+                }
+                Expr::Constant(ast::ExprConstant {
+                    value: ast::Constant::Ellipsis,
+                    ..
+                }) => continue,
+                _ => Intent::Other,
+            },
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                decorator_list,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                decorator_list,
+                ..
+            }) => {
+                if decorator_list.iter().any(is_property_decorator) {
+                    Intent::Properties
+                } else if is_dunder(name) {
+                    Intent::DunderOverrides
+                } else {
+                    Intent::Methods
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign { annotation, .. })
+                if signature::is_classvar(annotation) =>
+            {
+                Intent::ClassConstants
+            }
+            Stmt::AnnAssign(_) => Intent::IgnoredFields,
+            Stmt::Assign(_) | Stmt::AugAssign(_) => Intent::ClassConstants,
+            _ => Intent::Other,
+        };
+        intents.push(intent);
+    }
+    if intents.is_empty() {
+        intents.push(if has_docstring {
+            Intent::DocstringOnly
+        } else {
+            Intent::Empty
+        });
+    }
+    intents.sort();
+    intents.dedup();
+    intents
+}
+
+pub fn is_dunder(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+/// `@property`, `@cached_property`, `@functools.cached_property` and
+/// `@<name>.setter`/`.getter`/`.deleter`.
+fn is_property_decorator(decorator: &Expr) -> bool {
+    match decorator {
+        Expr::Name(ast::ExprName { id, .. }) => {
+            matches!(id.as_str(), "property" | "cached_property")
+        }
+        Expr::Attribute(ast::ExprAttribute { attr, .. }) => matches!(
+            attr.as_str(),
+            "cached_property" | "setter" | "getter" | "deleter"
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::Parse;
+
+    fn intents(body: &str) -> Vec<Intent> {
+        let text = format!("class A(B):\n{body}");
+        let suite = ast::Suite::parse(&text, "m.py").unwrap();
+        let [Stmt::ClassDef(class)] = suite.as_slice() else {
+            panic!("expected one class");
+        };
+        classify(&class.body)
+    }
+
+    #[test]
+    fn empty_bodies() {
+        assert_eq!(intents("    pass\n"), [Intent::Empty]);
+        assert_eq!(intents("    ...\n"), [Intent::Empty]);
+        assert_eq!(intents("    \"\"\"Doc.\"\"\"\n"), [Intent::DocstringOnly]);
+    }
+
+    #[test]
+    fn members() {
+        let body = "    \"\"\"Doc.\"\"\"\n    z: int\n    unit: ClassVar[str] = 'm'\n    ORIGIN = 0\n    @property\n    def norm(self): ...\n    def scale(self, k): ...\n    def __repr__(self): ...\n";
+        assert_eq!(
+            intents(body),
+            [
+                Intent::Methods,
+                Intent::Properties,
+                Intent::IgnoredFields,
+                Intent::DunderOverrides,
+                Intent::ClassConstants,
+            ]
+        );
+        assert_eq!(
+            intents("    @functools.cached_property\n    def norm(self): ...\n"),
+            [Intent::Properties]
+        );
+        assert_eq!(intents("    class Inner: ...\n"), [Intent::Other]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Report Python classes that inherit from typed named tuples.
+//!
+//! Subclassing a `typing.NamedTuple` class was never intended to be supported:
+//! the subclass keeps the parent's constructor, drops any fields it declares,
+//! and behaves differently from the parent around `super()`. See
+//! [python/typing#427](https://github.com/python/typing/issues/427).
+
+pub mod archive;
+pub mod baseline;
+pub mod config;
+pub mod conformance;
+pub mod diagnostic;
+pub mod discover;
+pub mod error;
+pub mod fix;
+pub mod imports;
+pub mod intent;
+pub mod mine;
+pub mod module;
+pub mod mro;
+pub mod project;
+pub mod report;
+pub mod rules;
+pub mod runtime;
+pub mod signature;
+pub mod simulate;
+pub mod source;
+pub mod suppression;
+pub mod verify;
+pub mod visit;
+
+pub use diagnostic::Diagnostic;
+pub use error::{Error, Result};
+pub use project::Project;
+pub use rules::Rule;
@@ -0,0 +1,564 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+
+use check_namedtuple_subclasses::baseline::{Baseline, Entry};
+use check_namedtuple_subclasses::config::Config;
+use check_namedtuple_subclasses::conformance;
+use check_namedtuple_subclasses::fix::{self, ClassFix, Outcome, Strategy};
+use check_namedtuple_subclasses::report::{self, Format};
+use check_namedtuple_subclasses::runtime::VersionRange;
+use check_namedtuple_subclasses::simulate::{self, Cause, Simulation};
+use check_namedtuple_subclasses::source::SourceFile;
+use check_namedtuple_subclasses::verify::{self, Runner};
+use check_namedtuple_subclasses::{discover, mine, rules, suppression, Error, Project, Rule};
+
+#[derive(Parser)]
+#[command(name = "check-namedtuple-subclasses", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Report classes that inherit from typed named tuples.
+    Check(CheckArgs),
+    /// Add `@final` to every typed named tuple, printing the changes as a diff.
+    AddFinal(AddFinalArgs),
+    /// Count typed named tuples and their subclasses across a corpus of projects.
+    Mine(MineArgs),
+    /// Show which modules would fail to import if inheriting from a typed
+    /// named tuple raised `TypeError`.
+    Simulate(SimulateArgs),
+    /// Write conformance tests for type checkers, marking the subclasses a
+    /// checker should report if typed named tuples were final.
+    Conformance(ConformanceArgs),
+    /// Run a minimal reproducer of each finding in the local `python3.x`
+    /// interpreters and report where the checker's prediction is wrong.
+    ///
+    /// A reproducer is the reported class statement with its fields, type
+    /// parameters, bases and metaclass as written, after the classes its
+    /// bases resolve to; for NTS001 and NTS002 only the bases leading to the
+    /// named tuple are kept. Non-literal defaults become `None` and annotations
+    /// aren't evaluated. What's checked is whether the class statement
+    /// raises and which exception, and the constructor's parameter names and
+    /// which have defaults. Not checked: annotation and default values,
+    /// methods other than NTS003's `super()` or `__class__` use, other
+    /// module-level code, NTS005 findings, and predictions for versions where
+    /// the proposal applies.
+    Verify(VerifyArgs),
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    /// Files or directories to check.
+    #[arg(default_value = ".")]
+    paths: Vec<PathBuf>,
+
+    /// Rules to run, by code or name, instead of the default set.
+    #[arg(long, value_delimiter = ',', value_name = "RULES")]
+    select: Option<Vec<Rule>>,
+
+    /// Rules to run on top of the selected ones, e.g. `NTS002`.
+    #[arg(long, value_delimiter = ',', value_name = "RULES")]
+    extend_select: Vec<Rule>,
+
+    /// Rewrite reported classes, then report what's left.
+    #[arg(long)]
+    fix: bool,
+
+    /// How `--fix` rewrites: `standalone` turns field-adding subclasses into
+    /// typed named tuples of their own, `dataclass` turns whole hierarchies
+    /// into frozen dataclasses.
+    #[arg(long, default_value_t = Strategy::Standalone)]
+    fix_strategy: Strategy,
+
+    /// The `pyproject.toml` to read settings from, instead of the closest one
+    /// with a `[tool.check-namedtuple-subclasses]` table.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// How to print diagnostics: `text`, `json`, `json-lines`, `sarif`, `csv`
+    /// or `markdown`.
+    #[arg(long, default_value_t = Format::Text)]
+    output_format: Format,
+
+    /// Report only findings not recorded in this baseline file.
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Say for each finding whether the code raises, misbehaves or works on
+    /// these Python versions, e.g. `3.8..3.15` or `3.12`.
+    #[arg(long, value_name = "VERSIONS")]
+    target_version: Option<VersionRange>,
+
+    /// Record the current findings in the `--baseline` file instead of
+    /// reporting them.
+    #[arg(long, requires = "baseline")]
+    write_baseline: bool,
+}
+
+#[derive(Args)]
+struct AddFinalArgs {
+    /// Files or directories to change.
+    #[arg(default_value = ".")]
+    paths: Vec<PathBuf>,
+
+    /// Write the changes back instead of only printing the diff.
+    #[arg(long)]
+    write: bool,
+
+    /// The `pyproject.toml` to read settings from, instead of the closest one
+    /// with a `[tool.check-namedtuple-subclasses]` table.
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct MineArgs {
+    /// Directory holding one unpacked project per subdirectory.
+    root: PathBuf,
+
+    /// Where to write the JSON summary instead of standard output.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SimulateArgs {
+    /// Files or directories making up the code base, tests included.
+    #[arg(default_value = ".")]
+    paths: Vec<PathBuf>,
+
+    /// The `pyproject.toml` to read settings from, instead of the closest one
+    /// with a `[tool.check-namedtuple-subclasses]` table.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct ConformanceArgs {
+    /// Directory to write `tests/` and `results/` into.
+    #[arg(default_value = "conformance")]
+    output: PathBuf,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// Files or directories to check.
+    #[arg(default_value = ".")]
+    paths: Vec<PathBuf>,
+
+    /// The `pyproject.toml` to read settings from, instead of the closest one
+    /// with a `[tool.check-namedtuple-subclasses]` table.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// An interpreter to verify with on top of the `python3.x` binaries found
+    /// on `PATH` and in pyenv. Can be given more than once.
+    #[arg(long, value_name = "PATH")]
+    python: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Check(args) => check(args),
+        Command::AddFinal(args) => add_final(args),
+        Command::Mine(args) => mine(args),
+        Command::Simulate(args) => simulate(args),
+        Command::Conformance(args) => write_conformance(args),
+        Command::Verify(args) => verify(args),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn check(args: CheckArgs) -> check_namedtuple_subclasses::Result<ExitCode> {
+    let config = load_config(args.config.as_deref(), &args.paths)?;
+    let selected = config.selection(args.select, &args.extend_select);
+    let (mut project, mut errors) = load(&args.paths, &config)?;
+    if args.fix {
+        let enabled = |source: &SourceFile, line| {
+            let rule = Rule::NamedTupleSubclass;
+            config
+                .rules_for(&selected, Path::new(source.path()))
+                .contains(&rule)
+                && !suppression::skips_file(source)
+                && !suppression::is_suppressed(source, rule, line)
+        };
+        apply_fixes(&project, args.fix_strategy, enabled)?;
+        (project, errors) = load(&args.paths, &config)?;
+    }
+    for err in &errors {
+        eprintln!("error: {err}");
+    }
+    let mut diagnostics = rules::check(&project, Rule::ALL);
+    diagnostics.retain(|diagnostic| {
+        config
+            .rules_for(&selected, Path::new(&diagnostic.path))
+            .contains(&diagnostic.rule)
+    });
+    if let Some(path) = &args.baseline {
+        if args.write_baseline {
+            Baseline::new(&project, &diagnostics, config.root()).write(path)?;
+            eprintln!(
+                "Recorded {} findings in {}.",
+                diagnostics.len(),
+                path.display()
+            );
+            return Ok(if errors.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(2)
+            });
+        }
+        let baseline = Baseline::read(path)?;
+        let is_run = |entry: &Entry| {
+            entry.code.parse().is_ok_and(|rule| {
+                config
+                    .rules_for(&selected, &config.root().join(&entry.path))
+                    .contains(&rule)
+            })
+        };
+        for entry in baseline.filter(&project, &mut diagnostics, config.root(), is_run) {
+            eprintln!(
+                "{}:{}: {} in `{}` is fixed; remove it from the baseline",
+                entry.path, entry.line, entry.code, entry.symbol
+            );
+        }
+    }
+    if let Some(range) = args.target_version.or(config.target_version()) {
+        for diagnostic in &mut diagnostics {
+            if let Some(behaviour) = diagnostic.behaviour {
+                diagnostic.runtime = behaviour.outcomes(range);
+            }
+        }
+    }
+    let mut stdout = io::stdout().lock();
+    report::write(args.output_format, &diagnostics, config.root(), &mut stdout)?;
+    stdout.flush()?;
+    Ok(if !errors.is_empty() {
+        ExitCode::from(2)
+    } else if !diagnostics.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// The settings from `path`, or else from the closest `pyproject.toml` to
+/// the checked `paths`.
+fn load_config(
+    path: Option<&Path>,
+    paths: &[PathBuf],
+) -> check_namedtuple_subclasses::Result<Config> {
+    match path {
+        Some(path) => Config::load(path)?.ok_or_else(|| Error::Config {
+            path: path.display().to_string(),
+            message: "no [tool.check-namedtuple-subclasses] table".to_owned(),
+        }),
+        None => Config::find_for(paths),
+    }
+}
+
+/// Loads the files under `paths` that the settings include.
+fn load(
+    paths: &[PathBuf],
+    config: &Config,
+) -> check_namedtuple_subclasses::Result<(Project, Vec<Error>)> {
+    let mut files = discover::python_files(paths)?;
+    files.retain(|file| config.is_included(file));
+    Ok(Project::load_files(&files))
+}
+
+/// Writes the rewrites of classes `enabled` accepts by file and line,
+/// reporting the classes that were left alone on stderr.
+fn apply_fixes(
+    project: &Project,
+    strategy: Strategy,
+    enabled: impl Fn(&SourceFile, usize) -> bool,
+) -> io::Result<()> {
+    let sources: HashMap<&str, &SourceFile> = project
+        .modules()
+        .map(|(_, module)| (module.source.path(), &module.source))
+        .collect();
+    let mut fixes = strategy.fixes(project);
+    fixes.retain(|class_fix| enabled(sources[class_fix.path.as_str()], class_fix.location.line));
+    let fixed = report_skipped(&fixes);
+    let sources = project.modules().map(|(_, module)| &module.source);
+    match fix::apply(sources, &fixes) {
+        Ok(files) => {
+            for (path, text) in files {
+                std::fs::write(path, text)?;
+            }
+            if !fixes.is_empty() {
+                eprintln!("Rewrote {fixed} of {} classes.", fixes.len());
+            }
+        }
+        Err(overlapping) => {
+            report_overlapping(&fixes, &overlapping);
+            eprintln!("Rewrote 0 of {} classes.", fixes.len());
+        }
+    }
+    Ok(())
+}
+
+fn add_final(args: AddFinalArgs) -> check_namedtuple_subclasses::Result<ExitCode> {
+    let config = load_config(args.config.as_deref(), &args.paths)?;
+    let (project, errors) = load(&args.paths, &config)?;
+    for err in &errors {
+        eprintln!("error: {err}");
+    }
+    let mut fixes = fix::final_decorator::fixes(&project);
+    fixes.retain(|class_fix| {
+        project
+            .modules()
+            .find(|(_, module)| module.source.path() == class_fix.path)
+            .is_some_and(|(_, module)| !suppression::skips_file(&module.source))
+    });
+    report_skipped(&fixes);
+    let sources = project.modules().map(|(_, module)| &module.source);
+    let changed = match fix::apply(sources, &fixes) {
+        Ok(changed) => changed,
+        Err(overlapping) => {
+            report_overlapping(&fixes, &overlapping);
+            return Ok(ExitCode::from(2));
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    for (path, text) in &changed {
+        if args.write {
+            std::fs::write(path, text)?;
+        } else {
+            let (_, module) = project
+                .modules()
+                .find(|(_, module)| module.source.path() == path)
+                .expect("fixes only change loaded modules");
+            let diff = fix::unified_diff(path, module.source.text(), text);
+            stdout.write_all(diff.as_bytes())?;
+        }
+    }
+    stdout.flush()?;
+    Ok(if !errors.is_empty() {
+        ExitCode::from(2)
+    } else if !args.write && !changed.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Prints why classes were left alone on stderr, returning how many weren't.
+fn report_skipped(fixes: &[ClassFix]) -> usize {
+    let mut fixed = 0;
+    for class_fix in fixes {
+        match &class_fix.outcome {
+            Outcome::Fixed(_) => fixed += 1,
+            Outcome::Skipped(reason) => eprintln!(
+                "{}:{}: not fixing `{}`: {reason}",
+                class_fix.path, class_fix.location, class_fix.qualname
+            ),
+        }
+    }
+    fixed
+}
+
+/// Prints the fixes whose edits overlap others', which kept any from being
+/// written.
+fn report_overlapping(fixes: &[ClassFix], overlapping: &[usize]) {
+    for &index in overlapping {
+        let class_fix = &fixes[index];
+        eprintln!(
+            "{}:{}: not fixing `{}`: its edits overlap another rewrite's, so nothing was written",
+            class_fix.path, class_fix.location, class_fix.qualname
+        );
+    }
+}
+
+fn mine(args: MineArgs) -> check_namedtuple_subclasses::Result<ExitCode> {
+    let summary = mine::mine(&args.root)?;
+    let mut json = serde_json::to_string_pretty(&summary).map_err(io::Error::from)?;
+    json.push('\n');
+    match args.output {
+        Some(path) => std::fs::write(path, json)?,
+        None => io::stdout().lock().write_all(json.as_bytes())?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn simulate(args: SimulateArgs) -> check_namedtuple_subclasses::Result<ExitCode> {
+    let config = load_config(args.config.as_deref(), &args.paths)?;
+    let (project, errors) = load(&args.paths, &config)?;
+    for err in &errors {
+        eprintln!("error: {err}");
+    }
+    let simulation = simulate::simulate(&project);
+    let mut stdout = io::stdout().lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut stdout, &simulation).map_err(io::Error::from)?;
+        writeln!(stdout)?;
+    } else {
+        write_simulation(&simulation, &mut stdout)?;
+    }
+    stdout.flush()?;
+    Ok(if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(2)
+    })
+}
+
+fn write_simulation(simulation: &Simulation, out: &mut dyn Write) -> io::Result<()> {
+    for package in &simulation.packages {
+        write!(
+            out,
+            "{}: {} of {} modules fail to import",
+            package.name,
+            package.failing.len(),
+            package.modules
+        )?;
+        if package.test_modules > 0 {
+            write!(
+                out,
+                " ({} of {} test modules)",
+                package.failing_tests(),
+                package.test_modules
+            )?;
+        }
+        writeln!(out)?;
+        for failure in &package.failing {
+            let test = if failure.is_test { " [test]" } else { "" };
+            match &failure.cause {
+                Cause::Defines(subclass) => writeln!(
+                    out,
+                    "  {}{test}: `{}` at {}:{}",
+                    failure.module,
+                    subclass.qualname,
+                    subclass.path,
+                    subclass.location()
+                )?,
+                Cause::Imports(module) => {
+                    writeln!(out, "  {}{test}: imports `{module}`", failure.module)?
+                }
+            }
+        }
+        for subclass in &package.deferred {
+            writeln!(
+                out,
+                "  `{}` at {}:{} fails only when its function runs",
+                subclass.qualname,
+                subclass.path,
+                subclass.location()
+            )?;
+        }
+    }
+    writeln!(
+        out,
+        "{} of {} modules fail to import.",
+        simulation.failing(),
+        simulation.modules()
+    )
+}
+
+fn write_conformance(args: ConformanceArgs) -> check_namedtuple_subclasses::Result<ExitCode> {
+    for path in conformance::write(&args.output)? {
+        println!("{}", path.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn verify(args: VerifyArgs) -> check_namedtuple_subclasses::Result<ExitCode> {
+    let interpreters = verify::interpreters(&args.python);
+    if interpreters.is_empty() {
+        eprintln!(
+            "error: no python3.x interpreter found on PATH or in pyenv; pass one with --python"
+        );
+        return Ok(ExitCode::from(2));
+    }
+    let config = load_config(args.config.as_deref(), &args.paths)?;
+    let (project, mut errors) = load(&args.paths, &config)?;
+    for err in &errors {
+        eprintln!("error: {err}");
+    }
+    let selected = config.selection(None, &[]);
+    let mut diagnostics = rules::check(&project, Rule::ALL);
+    diagnostics.retain(|diagnostic| {
+        config
+            .rules_for(&selected, Path::new(&diagnostic.path))
+            .contains(&diagnostic.rule)
+    });
+    let mut runner = Runner::default();
+    let mut verified = 0;
+    let mut mismatches = 0;
+    let mut uncovered: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut stdout = io::stdout().lock();
+    for diagnostic in &diagnostics {
+        let Some(reproducer) = verify::reproducer(&project, diagnostic) else {
+            *uncovered.entry(diagnostic.rule.code()).or_default() += 1;
+            continue;
+        };
+        verified += 1;
+        for interpreter in &interpreters {
+            if reproducer.behaviour.is_proposed(interpreter.version) {
+                continue;
+            }
+            let observation = match runner.observe(interpreter, &reproducer) {
+                Ok(observation) => observation,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    errors.push(err.into());
+                    continue;
+                }
+            };
+            if let Some(difference) = verify::compare(&reproducer, interpreter.version, observation)
+            {
+                mismatches += 1;
+                writeln!(
+                    stdout,
+                    "{}:{}: {} checker bug on Python {}: {difference}",
+                    diagnostic.path,
+                    diagnostic.location,
+                    diagnostic.rule.code(),
+                    interpreter.version
+                )?;
+            }
+        }
+    }
+    let versions: Vec<String> = interpreters
+        .iter()
+        .map(|interpreter| interpreter.version.to_string())
+        .collect();
+    writeln!(
+        stdout,
+        "Verified {verified} findings on Python {}: {mismatches} mismatches.",
+        versions.join(", ")
+    )?;
+    for (code, count) in uncovered {
+        writeln!(
+            stdout,
+            "Not verified: {count} {code} findings, which have no reproducer."
+        )?;
+    }
+    stdout.flush()?;
+    Ok(if !errors.is_empty() {
+        ExitCode::from(2)
+    } else if mismatches > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
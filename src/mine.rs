@@ -0,0 +1,317 @@
+//! Aggregate statistics over a corpus of many projects.
+//!
+//! Every directory or archive directly under the corpus root is one project.
+//! The summary is deterministic for a given corpus and tool version: projects
+//! are sorted, and the corpus is identified by a hash of its files rather
+//! than by where it happens to be unpacked.
+//!
+//! Vendored and copied modules are counted once in the totals. Files are
+//! fingerprinted by their normalised content, and each finding in a copy is
+//! listed once with every location it appears at.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::archive;
+use crate::diagnostic::Diagnostic;
+use crate::discover;
+use crate::error::{Error, Result};
+use crate::intent::Intent;
+use crate::project::{ClassKind, Factory, ModuleId, Project};
+use crate::rules::{self, Rule};
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub tool: Tool,
+    pub corpus: Corpus,
+    /// Counts with every distinct file counted once, however many projects
+    /// carry a copy of it.
+    pub totals: Counts,
+    pub projects: Vec<ProjectRow>,
+    /// Subclass findings, each distinct one listed once.
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tool {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Corpus {
+    /// SHA-256 over the sorted relative paths and contents of all Python
+    /// files and archives.
+    pub sha256: String,
+    pub projects: usize,
+    /// Python sources whose normalised content already appeared elsewhere.
+    /// Empty and whitespace-only files are never counted as copies.
+    pub duplicate_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Counts {
+    /// Python sources, including those read from archives and those that
+    /// failed to parse.
+    pub files: usize,
+    /// Sources among `files` that aren't valid Python.
+    pub parse_errors: usize,
+    /// Files and archives that couldn't be read at all, and so aren't
+    /// counted in `files`.
+    pub read_errors: usize,
+    /// Class-syntax and functional-form `typing.NamedTuple` types.
+    pub typed_namedtuples: usize,
+    /// Classes inheriting from them, as reported by NTS001.
+    pub subclasses: usize,
+    /// Subclasses per intent tag; a subclass with several tags counts once
+    /// under each.
+    pub intents: BTreeMap<&'static str, usize>,
+}
+
+impl Default for Counts {
+    fn default() -> Self {
+        Self {
+            files: 0,
+            parse_errors: 0,
+            read_errors: 0,
+            typed_namedtuples: 0,
+            subclasses: 0,
+            intents: Intent::ALL
+                .iter()
+                .map(|intent| (intent.as_str(), 0))
+                .collect(),
+        }
+    }
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.files += other.files;
+        self.parse_errors += other.parse_errors;
+        self.read_errors += other.read_errors;
+        self.typed_namedtuples += other.typed_namedtuples;
+        self.subclasses += other.subclasses;
+        for (intent, count) in &other.intents {
+            *self.intents.entry(intent).or_default() += count;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectRow {
+    pub name: String,
+    /// Counts for this project alone, copies of files from other projects
+    /// included.
+    #[serde(flatten)]
+    pub counts: Counts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    /// The fingerprint of the file the finding is in.
+    pub fingerprint: String,
+    pub code: &'static str,
+    /// The message at the first location.
+    pub message: String,
+    pub intents: Vec<&'static str>,
+    /// Every `path:line:column` the finding occurs at, relative to the
+    /// corpus root.
+    pub locations: Vec<String>,
+}
+
+/// Scans every project under `root`.
+pub fn mine(root: &Path) -> Result<Summary> {
+    let mut miner = Miner {
+        root,
+        totals: Counts::default(),
+        duplicate_files: 0,
+        fingerprints: HashSet::new(),
+        findings: Vec::new(),
+        finding_ids: HashMap::new(),
+    };
+    let mut hasher = Sha256::new();
+    let mut projects = Vec::new();
+    for (name, paths) in corpus_projects(root)? {
+        for file in discover::python_files(&paths)? {
+            hasher.update(miner.relative(&file).as_bytes());
+            hasher.update([0]);
+            hasher.update(Sha256::digest(std::fs::read(&file)?));
+        }
+        let counts = miner.project(&paths)?;
+        projects.push(ProjectRow { name, counts });
+    }
+    Ok(Summary {
+        tool: Tool {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        corpus: Corpus {
+            sha256: format!("{:x}", hasher.finalize()),
+            projects: projects.len(),
+            duplicate_files: miner.duplicate_files,
+        },
+        totals: miner.totals,
+        projects,
+        findings: miner.findings,
+    })
+}
+
+/// The projects under `root` by name, with the paths making them up. Python
+/// files directly in `root` form a project named `.`.
+fn corpus_projects(root: &Path) -> Result<Vec<(String, Vec<PathBuf>)>> {
+    let mut projects = Vec::new();
+    let mut loose = Vec::new();
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() || archive::is_archive(&path) {
+            projects.push((name, vec![path]));
+        } else if discover::is_python_file(&path) {
+            loose.push(path);
+        }
+    }
+    if !loose.is_empty() {
+        projects.push((".".to_owned(), loose));
+    }
+    projects.sort();
+    Ok(projects)
+}
+
+/// A fingerprint of `text` that ignores line endings, trailing whitespace
+/// and blank lines, so that copies differing only in those match.
+pub fn fingerprint(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for line in text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+    {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+struct Miner<'a> {
+    root: &'a Path,
+    totals: Counts,
+    duplicate_files: usize,
+    fingerprints: HashSet<String>,
+    findings: Vec<Finding>,
+    /// Indices into `findings` by file fingerprint and the finding's own
+    /// content: its code, message and `line:column` within the file.
+    finding_ids: HashMap<(String, &'static str, String, String), usize>,
+}
+
+impl Miner<'_> {
+    /// Counts one project, adding the files not seen before to the totals.
+    fn project(&mut self, paths: &[PathBuf]) -> Result<Counts> {
+        let (project, errors) = Project::load(paths)?;
+        let unparsable = errors
+            .iter()
+            .filter(|err| matches!(err, Error::Parse { .. }))
+            .count();
+        let mut counts = Counts {
+            files: unparsable,
+            parse_errors: unparsable,
+            read_errors: errors.len() - unparsable,
+            ..Counts::default()
+        };
+        self.totals.add(&counts);
+
+        let mut files: HashMap<ModuleId, Counts> = HashMap::new();
+        let mut ids = HashMap::new();
+        for (id, module) in project.modules() {
+            ids.insert(module.source.path(), id);
+            let file = files.entry(id).or_default();
+            file.files = 1;
+            file.typed_namedtuples += project
+                .assigned_namedtuples(id)
+                .iter()
+                .filter(|functional| functional.factory == Factory::Typing)
+                .count();
+        }
+        for (id, _) in project.classes() {
+            if project.class_kind(id) == ClassKind::TypedNamedTuple {
+                files.entry(id.module).or_default().typed_namedtuples += 1;
+            }
+        }
+        let diagnostics = rules::check(&project, &[Rule::NamedTupleSubclass]);
+        let mut by_module: HashMap<ModuleId, Vec<&Diagnostic>> = HashMap::new();
+        for diagnostic in &diagnostics {
+            let id = ids[diagnostic.path.as_str()];
+            let file = files.entry(id).or_default();
+            file.subclasses += 1;
+            for intent in &diagnostic.intents {
+                *file.intents.entry(intent.as_str()).or_default() += 1;
+            }
+            by_module.entry(id).or_default().push(diagnostic);
+        }
+
+        for (id, module) in project.modules() {
+            let file = &files[&id];
+            counts.add(file);
+            let fingerprint = fingerprint(module.source.text());
+            let diagnostics = by_module.get(&id).map_or(&[][..], Vec::as_slice);
+            for diagnostic in diagnostics {
+                self.record(&fingerprint, diagnostic);
+            }
+            // Empty files, like most `__init__.py`, are alike without being
+            // copies of each other.
+            let is_empty = module.source.text().trim().is_empty();
+            if is_empty || self.fingerprints.insert(fingerprint) {
+                self.totals.add(file);
+            } else {
+                self.duplicate_files += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    fn record(&mut self, fingerprint: &str, diagnostic: &Diagnostic) {
+        let location = format!(
+            "{}:{}",
+            self.relative(Path::new(&diagnostic.path)),
+            diagnostic.location
+        );
+        let key = (
+            fingerprint.to_owned(),
+            diagnostic.rule.code(),
+            diagnostic.message.clone(),
+            diagnostic.location.to_string(),
+        );
+        if let Some(&index) = self.finding_ids.get(&key) {
+            self.findings[index].locations.push(location);
+            return;
+        }
+        self.finding_ids.insert(key, self.findings.len());
+        self.findings.push(Finding {
+            fingerprint: fingerprint.to_owned(),
+            code: diagnostic.rule.code(),
+            message: diagnostic.message.clone(),
+            intents: diagnostic
+                .intents
+                .iter()
+                .map(|intent| intent.as_str())
+                .collect(),
+            locations: vec![location],
+        });
+    }
+
+    /// `path` relative to the corpus root, with `/` separators.
+    fn relative(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(self.root).unwrap_or(path);
+        let components: Vec<_> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        components.join("/")
+    }
+}
@@ -0,0 +1,485 @@
+//! Per-module symbol tables.
+
+use std::collections::HashMap;
+
+use rustpython_parser::ast::{self, Stmt};
+use rustpython_parser::Parse;
+
+use crate::error::{Error, Result};
+use crate::imports::{self, ModulePath};
+use crate::source::{Location, SourceFile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScopeId(usize);
+
+impl ScopeId {
+    /// The module's own top-level scope.
+    pub const MODULE: ScopeId = ScopeId(0);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    Module,
+    Class,
+    Function,
+}
+
+/// What a name is bound to in a scope.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    /// `import a.b` binds `a` to the module `a`, `import a.b as c` binds `c`
+    /// to the module `a.b`.
+    Module(String),
+    /// `from module import name`.
+    ImportFrom { module: String, name: String },
+    /// A class statement, by its index in [`Module::classes`].
+    Class(usize),
+    /// `name = value`, evaluated lazily in the scope of the assignment.
+    Assign(Box<ast::Expr>),
+    /// Any other assignment. It shadows earlier bindings but can't be followed.
+    Opaque,
+}
+
+#[derive(Debug)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    bindings: HashMap<String, Vec<Binding>>,
+}
+
+impl Scope {
+    /// Everything `name` may be bound to in this scope.
+    ///
+    /// A name bound in different branches of an `if` or `try` statement, such
+    /// as an `if TYPE_CHECKING:` import with a runtime fallback, has one
+    /// alternative per branch. An unconditional binding replaces all earlier
+    /// ones.
+    pub fn bindings(&self, name: &str) -> &[Binding] {
+        self.bindings.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every binding in the scope, in no particular order.
+    pub fn all_bindings(&self) -> impl Iterator<Item = (&str, &Binding)> {
+        self.bindings
+            .iter()
+            .flat_map(|(name, bindings)| bindings.iter().map(move |b| (name.as_str(), b)))
+    }
+}
+
+/// A class statement found anywhere in a module.
+#[derive(Debug)]
+pub struct ClassDef {
+    pub name: String,
+    /// Dotted path from the module top level, e.g. `Outer.Inner`.
+    pub qualname: String,
+    /// The scope the class statement appears in, where its bases are evaluated.
+    pub scope: ScopeId,
+    pub node: ast::StmtClassDef,
+}
+
+#[derive(Debug)]
+pub struct Module {
+    /// Dotted module name, e.g. `pkg.models`.
+    pub name: String,
+    /// Whether this is a package's `__init__.py` or `__init__.pyi`.
+    pub is_package: bool,
+    pub source: SourceFile,
+    /// The top-level statements.
+    pub body: ast::Suite,
+    pub scopes: Vec<Scope>,
+    pub classes: Vec<ClassDef>,
+    /// Absolute names of the modules this one does `from ... import *` on.
+    pub star_imports: Vec<String>,
+    /// The names listed in `__all__`, if the module defines it.
+    pub all: Option<Vec<String>>,
+}
+
+impl Module {
+    pub fn parse(path: ModulePath, source: SourceFile) -> Result<Self> {
+        let suite =
+            ast::Suite::parse(source.text(), source.path()).map_err(|err| Error::Parse {
+                path: source.path().to_owned(),
+                location: source.location(err.offset),
+                message: err.error.to_string(),
+            })?;
+        let mut builder = Builder {
+            scopes: vec![Scope {
+                kind: ScopeKind::Module,
+                parent: None,
+                bindings: HashMap::new(),
+            }],
+            classes: Vec::new(),
+            qualname: Vec::new(),
+            branch_depth: 0,
+            path: &path,
+            star_imports: Vec::new(),
+            all: None,
+        };
+        builder.visit_body(ScopeId::MODULE, &suite);
+        let Builder {
+            scopes,
+            classes,
+            star_imports,
+            all,
+            ..
+        } = builder;
+        Ok(Self {
+            name: path.name,
+            is_package: path.is_package,
+            source,
+            body: suite,
+            scopes,
+            classes,
+            star_imports,
+            all,
+        })
+    }
+
+    /// The innermost class statement spanning `location`.
+    pub fn innermost_class(&self, location: Location) -> Option<&ClassDef> {
+        self.classes
+            .iter()
+            .filter(|class| {
+                let start = self.source.location(class.node.range.start());
+                let end = self.source.location(class.node.range.end());
+                (start..=end).contains(&location)
+            })
+            .max_by_key(|class| class.node.range.start())
+    }
+
+    /// The archive the module was read from, as it appears in the module's
+    /// path, or `""` for a file on disk.
+    pub fn archive(&self) -> &str {
+        let path = self.source.path();
+        path.rfind('!').map_or("", |end| &path[..end])
+    }
+
+    /// Whether this is a `.pyi` stub rather than the module's source.
+    pub fn is_stub(&self) -> bool {
+        self.source.path().ends_with(".pyi")
+    }
+
+    /// Whether `name` is exported by `from <this module> import *`.
+    pub fn exports(&self, name: &str) -> bool {
+        match &self.all {
+            Some(all) => all.iter().any(|exported| exported == name),
+            None => !name.starts_with('_'),
+        }
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    pub fn scope_ids(&self) -> impl Iterator<Item = ScopeId> {
+        (0..self.scopes.len()).map(ScopeId)
+    }
+
+    /// Looks `name` up the way Python does from code running in `scope`:
+    /// the scope itself, then enclosing function scopes, then the module.
+    ///
+    /// Returns the binding together with the scope it was found in.
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<(ScopeId, &[Binding])> {
+        let start = scope;
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            let scope = self.scope(id);
+            // Class scopes are only visible to code directly in their body.
+            if id == start || scope.kind != ScopeKind::Class {
+                let bindings = scope.bindings(name);
+                if !bindings.is_empty() {
+                    return Some((id, bindings));
+                }
+            }
+            current = scope.parent;
+        }
+        None
+    }
+}
+
+struct Builder<'a> {
+    scopes: Vec<Scope>,
+    classes: Vec<ClassDef>,
+    qualname: Vec<String>,
+    /// How many conditional blocks enclose the statement being visited
+    /// within the current scope.
+    branch_depth: usize,
+    path: &'a ModulePath,
+    star_imports: Vec<String>,
+    all: Option<Vec<String>>,
+}
+
+impl Builder<'_> {
+    fn push_scope(&mut self, kind: ScopeKind, parent: ScopeId) -> ScopeId {
+        self.scopes.push(Scope {
+            kind,
+            parent: Some(parent),
+            bindings: HashMap::new(),
+        });
+        ScopeId(self.scopes.len() - 1)
+    }
+
+    fn bind(&mut self, scope: ScopeId, name: &str, binding: Binding) {
+        let alternatives = self.scopes[scope.0]
+            .bindings
+            .entry(name.to_owned())
+            .or_default();
+        if self.branch_depth == 0 {
+            alternatives.clear();
+        }
+        if !alternatives.contains(&binding) {
+            alternatives.push(binding);
+        }
+    }
+
+    fn visit_body(&mut self, scope: ScopeId, body: &[Stmt]) {
+        for stmt in body {
+            self.visit_stmt(scope, stmt);
+        }
+    }
+
+    /// Visits a block that may or may not run.
+    fn visit_branch(&mut self, scope: ScopeId, body: &[Stmt]) {
+        self.branch_depth += 1;
+        self.visit_body(scope, body);
+        self.branch_depth -= 1;
+    }
+
+    /// Visits the body of a new class or function scope.
+    fn visit_scope(&mut self, scope: ScopeId, body: &[Stmt]) {
+        let branch_depth = std::mem::take(&mut self.branch_depth);
+        self.visit_body(scope, body);
+        self.branch_depth = branch_depth;
+    }
+
+    fn visit_stmt(&mut self, scope: ScopeId, stmt: &Stmt) {
+        match stmt {
+            Stmt::ClassDef(class) => {
+                let index = self.classes.len();
+                self.qualname.push(class.name.to_string());
+                self.classes.push(ClassDef {
+                    name: class.name.to_string(),
+                    qualname: self.qualname.join("."),
+                    scope,
+                    node: class.clone(),
+                });
+                let body_scope = self.push_scope(ScopeKind::Class, scope);
+                self.visit_scope(body_scope, &class.body);
+                self.qualname.pop();
+                self.bind(scope, &class.name, Binding::Class(index));
+            }
+            Stmt::FunctionDef(ast::StmtFunctionDef { name, body, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, body, .. }) => {
+                self.qualname.push(format!("{name}.<locals>"));
+                let body_scope = self.push_scope(ScopeKind::Function, scope);
+                self.visit_scope(body_scope, body);
+                self.qualname.pop();
+                self.bind(scope, name, Binding::Opaque);
+            }
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    match &alias.asname {
+                        Some(asname) => {
+                            self.bind(scope, asname, Binding::Module(alias.name.to_string()))
+                        }
+                        None => {
+                            let top = alias.name.split('.').next().unwrap_or(&alias.name);
+                            self.bind(scope, top, Binding::Module(top.to_owned()));
+                        }
+                    }
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module,
+                names,
+                level,
+                ..
+            }) => {
+                let level = level.map_or(0, |level| level.to_u32());
+                let module = imports::resolve_relative(
+                    self.path,
+                    level,
+                    module.as_ref().map(|m| m.as_str()),
+                );
+                for alias in names {
+                    if alias.name.as_str() == "*" {
+                        if let (Some(module), ScopeId::MODULE) = (&module, scope) {
+                            self.star_imports.push(module.clone());
+                        }
+                        continue;
+                    }
+                    let bound = alias.asname.as_ref().unwrap_or(&alias.name);
+                    let binding = match &module {
+                        Some(module) => Binding::ImportFrom {
+                            module: module.clone(),
+                            name: alias.name.to_string(),
+                        },
+                        None => Binding::Opaque,
+                    };
+                    self.bind(scope, bound, binding);
+                }
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                for target in targets {
+                    if scope == ScopeId::MODULE && is_dunder_all(target) {
+                        self.all = Some(string_sequence(value).unwrap_or_default());
+                    }
+                    self.bind_assignment(scope, target, value);
+                }
+            }
+            Stmt::AugAssign(ast::StmtAugAssign { target, value, .. })
+                if scope == ScopeId::MODULE && is_dunder_all(target) =>
+            {
+                self.extend_all(string_sequence(value));
+            }
+            Stmt::Expr(ast::StmtExpr { value, .. }) if scope == ScopeId::MODULE => {
+                // `__all__.extend([...])` and `__all__.append("...")`.
+                if let ast::Expr::Call(ast::ExprCall { func, args, .. }) = value.as_ref() {
+                    if let (ast::Expr::Attribute(ast::ExprAttribute { value, attr, .. }), [arg]) =
+                        (func.as_ref(), args.as_slice())
+                    {
+                        match attr.as_str() {
+                            "extend" if is_dunder_all(value) => {
+                                self.extend_all(string_sequence(arg))
+                            }
+                            "append" if is_dunder_all(value) => {
+                                self.extend_all(string_literal(arg).map(|name| vec![name]))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                value: Some(value),
+                ..
+            }) => {
+                self.bind_assignment(scope, target, value);
+            }
+            Stmt::AugAssign(ast::StmtAugAssign { target, .. }) => {
+                self.bind_target(scope, target);
+            }
+            Stmt::TypeAlias(ast::StmtTypeAlias { name, .. }) => {
+                self.bind_target(scope, name);
+            }
+            Stmt::For(ast::StmtFor {
+                target,
+                body,
+                orelse,
+                ..
+            })
+            | Stmt::AsyncFor(ast::StmtAsyncFor {
+                target,
+                body,
+                orelse,
+                ..
+            }) => {
+                self.bind_target(scope, target);
+                self.visit_branch(scope, body);
+                self.visit_branch(scope, orelse);
+            }
+            Stmt::While(ast::StmtWhile { body, orelse, .. })
+            | Stmt::If(ast::StmtIf { body, orelse, .. }) => {
+                self.visit_branch(scope, body);
+                self.visit_branch(scope, orelse);
+            }
+            Stmt::With(ast::StmtWith { items, body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { items, body, .. }) => {
+                for item in items {
+                    if let Some(target) = &item.optional_vars {
+                        self.bind_target(scope, target);
+                    }
+                }
+                self.visit_body(scope, body);
+            }
+            Stmt::Match(ast::StmtMatch { cases, .. }) => {
+                for case in cases {
+                    self.visit_branch(scope, &case.body);
+                }
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                self.visit_branch(scope, body);
+                for ast::ExceptHandler::ExceptHandler(handler) in handlers {
+                    self.branch_depth += 1;
+                    if let Some(name) = &handler.name {
+                        self.bind(scope, name, Binding::Opaque);
+                    }
+                    self.visit_body(scope, &handler.body);
+                    self.branch_depth -= 1;
+                }
+                self.visit_branch(scope, orelse);
+                self.visit_body(scope, finalbody);
+            }
+            _ => {}
+        }
+    }
+
+    fn extend_all(&mut self, names: Option<Vec<String>>) {
+        if let Some(names) = names {
+            self.all.get_or_insert_with(Vec::new).extend(names);
+        }
+    }
+
+    /// Binds `target = value`, keeping the value when the target is a plain name.
+    fn bind_assignment(&mut self, scope: ScopeId, target: &ast::Expr, value: &ast::Expr) {
+        match target {
+            ast::Expr::Name(ast::ExprName { id, .. }) => {
+                self.bind(scope, id, Binding::Assign(Box::new(value.clone())))
+            }
+            _ => self.bind_target(scope, target),
+        }
+    }
+
+    fn bind_target(&mut self, scope: ScopeId, target: &ast::Expr) {
+        match target {
+            ast::Expr::Name(ast::ExprName { id, .. }) => self.bind(scope, id, Binding::Opaque),
+            ast::Expr::Tuple(ast::ExprTuple { elts, .. })
+            | ast::Expr::List(ast::ExprList { elts, .. }) => {
+                for elt in elts {
+                    self.bind_target(scope, elt);
+                }
+            }
+            ast::Expr::Starred(ast::ExprStarred { value, .. }) => self.bind_target(scope, value),
+            _ => {}
+        }
+    }
+}
+
+fn is_dunder_all(expr: &ast::Expr) -> bool {
+    matches!(expr, ast::Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "__all__")
+}
+
+fn string_literal(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Constant(ast::ExprConstant {
+            value: ast::Constant::Str(value),
+            ..
+        }) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// The strings in a list or tuple literal made only of string literals.
+fn string_sequence(expr: &ast::Expr) -> Option<Vec<String>> {
+    match expr {
+        ast::Expr::List(ast::ExprList { elts, .. })
+        | ast::Expr::Tuple(ast::ExprTuple { elts, .. }) => {
+            elts.iter().map(string_literal).collect()
+        }
+        _ => None,
+    }
+}
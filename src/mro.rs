@@ -0,0 +1,308 @@
+//! Static method resolution orders and instance layouts.
+//!
+//! Named tuples are `tuple` subclasses with empty `__slots__`, so combining
+//! one with other bases can fail when the class statement runs, either
+//! because the bases have no consistent C3 linearization or because another
+//! base brings its own instance layout. Both are modelled here the way
+//! `type.__new__` computes them.
+
+use std::fmt;
+
+use rustpython_parser::ast::{self, Expr, Ranged, Stmt};
+
+use crate::module::ScopeId;
+use crate::project::{ClassId, ClassKind, FunctionalNamedTuple, ModuleId, Project, Value};
+
+/// Guards linearization against inheritance cycles.
+const MAX_DEPTH: usize = 32;
+
+/// A class as it appears in a method resolution order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MroEntry {
+    Class(ClassId),
+    Functional(FunctionalNamedTuple),
+    Builtin(String),
+    Generic,
+    /// A class defined outside of the project, by its source text.
+    External(String),
+}
+
+impl MroEntry {
+    pub fn from_base(project: &Project, module: ModuleId, scope: ScopeId, base: &Expr) -> Self {
+        match project.resolve(module, scope, base) {
+            Value::Class(id) => MroEntry::Class(id),
+            Value::Functional(functional) => MroEntry::Functional(functional),
+            Value::Builtin(name) => MroEntry::Builtin(name),
+            Value::Generic => MroEntry::Generic,
+            _ => MroEntry::External(project.module(module).source.slice(base.range()).to_owned()),
+        }
+    }
+
+    pub fn name(&self, project: &Project) -> String {
+        match self {
+            MroEntry::Class(id) => project.class(*id).qualname.clone(),
+            MroEntry::Functional(functional) => functional.typename.clone(),
+            MroEntry::Builtin(name) | MroEntry::External(name) => name.clone(),
+            MroEntry::Generic => "Generic".to_owned(),
+        }
+    }
+
+    /// Whether this is a named tuple type, whose `__new__` sets the fields.
+    pub fn is_namedtuple(&self, project: &Project) -> bool {
+        match self {
+            MroEntry::Class(id) => project.class_kind(*id) == ClassKind::TypedNamedTuple,
+            MroEntry::Functional(_) => true,
+            _ => false,
+        }
+    }
+
+    fn object() -> Self {
+        MroEntry::Builtin("object".to_owned())
+    }
+
+    fn tuple() -> Self {
+        MroEntry::Builtin("tuple".to_owned())
+    }
+}
+
+/// The bases `type.__new__` couldn't linearize.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MroError {
+    pub bases: Vec<String>,
+}
+
+impl fmt::Display for MroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cannot create a consistent method resolution order (MRO) for bases {}",
+            self.bases.join(", ")
+        )
+    }
+}
+
+/// The MRO of a class statement, starting with the class itself.
+pub fn class_mro(project: &Project, class: ClassId) -> Result<Vec<MroEntry>, MroError> {
+    linearize(project, &MroEntry::Class(class), 0)
+}
+
+fn linearize(project: &Project, entry: &MroEntry, depth: usize) -> Result<Vec<MroEntry>, MroError> {
+    let object = MroEntry::object();
+    match entry {
+        _ if depth > MAX_DEPTH => Ok(vec![entry.clone(), object]),
+        MroEntry::Builtin(name) if name == "object" => Ok(vec![object]),
+        MroEntry::Class(id) if project.class_kind(*id) == ClassKind::TypedNamedTuple => {
+            // `NamedTupleMeta` replaces the bases with `tuple` (and `Generic`).
+            let class = project.class(*id);
+            let mut mro = vec![entry.clone(), MroEntry::tuple()];
+            let generic = class
+                .node
+                .bases
+                .iter()
+                .any(|base| project.resolve(id.module, class.scope, base) == Value::Generic)
+                || !class.node.type_params.is_empty();
+            if generic {
+                mro.push(MroEntry::Generic);
+            }
+            mro.push(object);
+            Ok(mro)
+        }
+        MroEntry::Class(id) => {
+            let class = project.class(*id);
+            let bases: Vec<MroEntry> = class
+                .node
+                .bases
+                .iter()
+                .map(|base| MroEntry::from_base(project, id.module, class.scope, base))
+                .collect();
+            if bases.is_empty() {
+                return Ok(vec![entry.clone(), object]);
+            }
+            let mut sequences = Vec::with_capacity(bases.len() + 1);
+            for base in &bases {
+                sequences.push(linearize(project, base, depth + 1)?);
+            }
+            sequences.push(bases.clone());
+            let mut mro = vec![entry.clone()];
+            mro.extend(merge(sequences).ok_or_else(|| MroError {
+                bases: bases.iter().map(|base| base.name(project)).collect(),
+            })?);
+            Ok(mro)
+        }
+        MroEntry::Functional(_) => Ok(vec![entry.clone(), MroEntry::tuple(), object]),
+        MroEntry::Builtin(_) | MroEntry::Generic | MroEntry::External(_) => {
+            Ok(vec![entry.clone(), object])
+        }
+    }
+}
+
+/// C3 merge: repeatedly takes the first head that isn't in any tail.
+fn merge(mut sequences: Vec<Vec<MroEntry>>) -> Option<Vec<MroEntry>> {
+    let mut result = Vec::new();
+    loop {
+        sequences.retain(|sequence| !sequence.is_empty());
+        if sequences.is_empty() {
+            return Some(result);
+        }
+        let head = sequences
+            .iter()
+            .map(|sequence| &sequence[0])
+            .find(|candidate| {
+                sequences
+                    .iter()
+                    .all(|sequence| !sequence[1..].contains(candidate))
+            })?
+            .clone();
+        for sequence in &mut sequences {
+            if sequence[0] == head {
+                sequence.remove(0);
+            }
+        }
+        result.push(head);
+    }
+}
+
+/// What an instance of a class holds beyond `object`'s header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// Nothing, or only a `__dict__`, which any layout can take on.
+    Empty,
+    /// `tuple`'s variable-size item storage, shared by all named tuples.
+    Tuple,
+    /// Slots declared by the given class.
+    Slots(ClassId),
+    /// A builtin with its own C struct, like `int` or `BaseException`.
+    Builtin(String),
+    /// A class outside of the project.
+    Unknown,
+}
+
+impl Layout {
+    /// Whether a class can't have both `self` and `other` among its bases.
+    pub fn conflicts_with(&self, other: &Layout) -> bool {
+        match (self, other) {
+            (Layout::Empty | Layout::Unknown, _) | (_, Layout::Empty | Layout::Unknown) => false,
+            _ => self != other,
+        }
+    }
+}
+
+pub fn layout(project: &Project, entry: &MroEntry) -> Layout {
+    layout_at(project, entry, 0)
+}
+
+fn layout_at(project: &Project, entry: &MroEntry, depth: usize) -> Layout {
+    if depth > MAX_DEPTH {
+        return Layout::Unknown;
+    }
+    match entry {
+        MroEntry::Functional(_) => Layout::Tuple,
+        MroEntry::Class(id) if project.class_kind(*id) == ClassKind::TypedNamedTuple => {
+            Layout::Tuple
+        }
+        MroEntry::Class(id) => {
+            let class = project.class(*id);
+            if has_nonempty_slots(&class.node.body) {
+                return Layout::Slots(*id);
+            }
+            class
+                .node
+                .bases
+                .iter()
+                .map(|base| {
+                    let base = MroEntry::from_base(project, id.module, class.scope, base);
+                    layout_at(project, &base, depth + 1)
+                })
+                .find(|layout| *layout != Layout::Empty)
+                .unwrap_or(Layout::Empty)
+        }
+        MroEntry::Builtin(name) => match name.as_str() {
+            "object" => Layout::Empty,
+            "tuple" => Layout::Tuple,
+            "BaseException" | "Exception" | "ValueError" | "TypeError" | "KeyError"
+            | "LookupError" | "RuntimeError" | "OSError" => {
+                Layout::Builtin("BaseException".to_owned())
+            }
+            _ => Layout::Builtin(name.clone()),
+        },
+        MroEntry::Generic => Layout::Empty,
+        MroEntry::External(_) => Layout::Unknown,
+    }
+}
+
+/// Whether a class body assigns `__slots__` anything but an empty literal.
+fn has_nonempty_slots(body: &[Stmt]) -> bool {
+    body.iter().any(|stmt| {
+        let (targets, value): (&[Expr], &Expr) = match stmt {
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => (targets, value),
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                value: Some(value),
+                ..
+            }) => (std::slice::from_ref(target), value),
+            _ => return false,
+        };
+        let is_slots = targets.iter().any(|target| {
+            matches!(target, Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "__slots__")
+        });
+        let is_empty = match value {
+            Expr::Tuple(ast::ExprTuple { elts, .. })
+            | Expr::List(ast::ExprList { elts, .. })
+            | Expr::Set(ast::ExprSet { elts, .. }) => elts.is_empty(),
+            Expr::Dict(ast::ExprDict { keys, .. }) => keys.is_empty(),
+            Expr::Constant(ast::ExprConstant {
+                value: ast::Constant::Str(value),
+                ..
+            }) => value.trim().is_empty(),
+            _ => false,
+        };
+        is_slots && !is_empty
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::tests::project;
+
+    fn mro(text: &str, class: &str) -> Result<Vec<String>, MroError> {
+        let project = project(&[("m", false, text)]);
+        let (id, _) = project.classes().find(|(_, c)| c.name == class).unwrap();
+        class_mro(&project, id).map(|mro| mro.iter().map(|entry| entry.name(&project)).collect())
+    }
+
+    const POINT: &str = "from typing import NamedTuple\nclass Point(NamedTuple):\n    x: int\n";
+
+    #[test]
+    fn namedtuple_with_mixin() {
+        let text = format!("{POINT}class Mixin: pass\nclass A(Point, Mixin): pass\n");
+        assert_eq!(
+            mro(&text, "A").unwrap(),
+            ["A", "Point", "tuple", "Mixin", "object"]
+        );
+    }
+
+    #[test]
+    fn inconsistent_mro() {
+        let text = format!("{POINT}class A(Point, tuple): pass\nclass B(tuple, Point): pass\n");
+        assert!(mro(&text, "A").is_ok());
+        let err = mro(&text, "B").unwrap_err();
+        assert_eq!(err.bases, ["tuple", "Point"]);
+    }
+
+    #[test]
+    fn layouts() {
+        let text = format!(
+            "{POINT}class Slotted:\n    __slots__ = ('a',)\nclass Empty:\n    __slots__ = ()\nclass Error(ValueError): pass\n"
+        );
+        let project = project(&[("m", false, &text)]);
+        let layout_of = |name: &str| {
+            let (id, _) = project.classes().find(|(_, c)| c.name == name).unwrap();
+            layout(&project, &MroEntry::Class(id))
+        };
+        assert_eq!(layout_of("Point"), Layout::Tuple);
+        assert!(Layout::Tuple.conflicts_with(&layout_of("Slotted")));
+        assert!(!Layout::Tuple.conflicts_with(&layout_of("Empty")));
+        assert!(Layout::Tuple.conflicts_with(&layout_of("Error")));
+    }
+}
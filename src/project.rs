@@ -0,0 +1,694 @@
+//! Resolving class bases across all modules being checked.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use rustpython_parser::ast::{self, Expr, Ranged};
+use rustpython_parser::text_size::TextSize;
+
+use crate::archive;
+use crate::discover;
+use crate::error::{Error, Result};
+use crate::imports::ModulePath;
+use crate::module::{Binding, ClassDef, Module, ScopeId};
+use crate::signature::{self, Field};
+use crate::source::SourceFile;
+
+/// How many imports and aliases resolution follows before giving up.
+/// Guards against import cycles.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModuleId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClassId {
+    pub module: ModuleId,
+    pub index: usize,
+}
+
+/// What an expression evaluates to, as far as static analysis can tell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Module(String),
+    /// `typing.NamedTuple` or `collections.namedtuple` itself.
+    Factory(Factory),
+    Class(ClassId),
+    /// `typing._NamedTuple`, the class `NamedTuple` stands for in bases
+    /// since Python 3.9.
+    PrivateNamedTuple,
+    /// `typing.NamedTupleMeta`, the metaclass building typed named tuples.
+    NamedTupleMeta,
+    /// `typing.Generic`, subscripted or not.
+    Generic,
+    /// A class from `builtins`, like `tuple` or `Exception`.
+    Builtin(String),
+    /// A named tuple type created by calling the factory directly.
+    Functional(FunctionalNamedTuple),
+    Unknown,
+}
+
+/// The two ways of making a named tuple type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Factory {
+    /// `typing.NamedTuple`, as a base class or called directly.
+    Typing,
+    /// `collections.namedtuple`.
+    Collections,
+}
+
+impl Factory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Factory::Typing => "typing.NamedTuple",
+            Factory::Collections => "collections.namedtuple",
+        }
+    }
+}
+
+impl fmt::Display for Factory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A named tuple type created like `NamedTuple("Foo", [("x", int)])`,
+/// `NamedTuple("Foo", x=int)` or `namedtuple("Foo", "x y")`, either assigned
+/// to a name or inline in a class statement's bases.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionalNamedTuple {
+    pub factory: Factory,
+    pub module: ModuleId,
+    /// The `typename` argument, or `<unnamed>` if it isn't a string literal.
+    pub typename: String,
+    /// Where the call starts.
+    pub offset: TextSize,
+    /// The fields, if they are given as literals.
+    pub fields: Option<Vec<Field>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassKind {
+    /// A class with `NamedTuple` among its bases.
+    TypedNamedTuple,
+    Regular,
+}
+
+#[derive(Debug, Default)]
+pub struct Project {
+    modules: Vec<Module>,
+    by_name: ModuleNames,
+}
+
+/// Modules by name. Every archive is a namespace of its own, so that two
+/// wheels that both contain `pkg/models.py` don't import each other's.
+/// Names that aren't in the importer's archive are looked up among all
+/// modules.
+#[derive(Debug, Default)]
+pub struct ModuleNames {
+    all: HashMap<String, ModuleId>,
+    archives: HashMap<(String, String), ModuleId>,
+}
+
+impl ModuleNames {
+    /// Adds `module`, which replaces a module of the same name if `replaces`
+    /// says so, and otherwise only takes a name that's still free.
+    pub fn insert(&mut self, id: ModuleId, module: &Module, replaces: impl Fn(ModuleId) -> bool) {
+        keep(self.all.entry(module.name.clone()), id, &replaces);
+        if !module.archive().is_empty() {
+            let key = (module.archive().to_owned(), module.name.clone());
+            keep(self.archives.entry(key), id, &replaces);
+        }
+    }
+
+    /// The module `name` as imported from a module in `archive`.
+    pub fn get(&self, archive: &str, name: &str) -> Option<ModuleId> {
+        if !archive.is_empty() {
+            if let Some(&id) = self.archives.get(&(archive.to_owned(), name.to_owned())) {
+                return Some(id);
+            }
+        }
+        self.all.get(name).copied()
+    }
+}
+
+fn keep<K>(entry: Entry<K, ModuleId>, id: ModuleId, replaces: impl Fn(ModuleId) -> bool) {
+    match entry {
+        Entry::Occupied(mut entry) => {
+            if replaces(*entry.get()) {
+                entry.insert(id);
+            }
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(id);
+        }
+    }
+}
+
+impl Project {
+    /// Parses every Python file under `paths`.
+    ///
+    /// Files that can't be read or parsed are returned separately so that one
+    /// broken file doesn't stop the rest from being checked.
+    pub fn load(paths: &[PathBuf]) -> Result<(Self, Vec<Error>)> {
+        Ok(Project::load_files(&discover::python_files(paths)?))
+    }
+
+    /// Like [`Project::load`], for files and archives that were already
+    /// discovered.
+    pub fn load_files(files: &[PathBuf]) -> (Self, Vec<Error>) {
+        let mut project = Project::default();
+        let mut errors = Vec::new();
+        for path in files {
+            let sources = if archive::is_archive(path) {
+                archive::read(path).map(|contents| {
+                    errors.extend(contents.errors.into_iter().map(Error::from));
+                    contents.sources
+                })
+            } else {
+                SourceFile::read(path).map(|source| vec![(ModulePath::for_file(path), source)])
+            };
+            let sources = match sources {
+                Ok(sources) => sources,
+                Err(err) => {
+                    errors.push(err.into());
+                    continue;
+                }
+            };
+            for (module_path, source) in sources {
+                match Module::parse(module_path, source) {
+                    Ok(module) => {
+                        project.add_module(module);
+                    }
+                    Err(err) => errors.push(err),
+                }
+            }
+        }
+        (project, errors)
+    }
+
+    /// Adds a parsed module. When several files have the same module name,
+    /// imports resolve to the first stub, or else to the first source, as
+    /// they do for type checkers. Imports from an archive's modules look in
+    /// that archive first.
+    pub fn add_module(&mut self, module: Module) -> ModuleId {
+        let id = ModuleId(self.modules.len());
+        let modules = &self.modules;
+        self.by_name.insert(id, &module, |other| {
+            module.is_stub() && !modules[other.0].is_stub()
+        });
+        self.modules.push(module);
+        id
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleId, &Module)> {
+        self.modules
+            .iter()
+            .enumerate()
+            .map(|(index, module)| (ModuleId(index), module))
+    }
+
+    pub fn module(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    /// The module `name` as imported by `importer`.
+    pub fn module_by_name(&self, importer: ModuleId, name: &str) -> Option<ModuleId> {
+        self.by_name.get(self.module(importer).archive(), name)
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = (ClassId, &ClassDef)> {
+        self.modules().flat_map(|(module, m)| {
+            m.classes
+                .iter()
+                .enumerate()
+                .map(move |(index, class)| (ClassId { module, index }, class))
+        })
+    }
+
+    pub fn class(&self, id: ClassId) -> &ClassDef {
+        &self.module(id.module).classes[id.index]
+    }
+
+    /// Evaluates `expr` as it appears in `scope` of `module`.
+    pub fn resolve(&self, module: ModuleId, scope: ScopeId, expr: &Expr) -> Value {
+        self.resolve_expr(module, scope, expr, 0)
+    }
+
+    /// Named tuple types created by assigning a factory call to a name, like
+    /// `Foo = NamedTuple("Foo", [("x", int)])`.
+    pub fn assigned_namedtuples(&self, module: ModuleId) -> Vec<FunctionalNamedTuple> {
+        let mut found = Vec::new();
+        let m = self.module(module);
+        for scope in m.scope_ids() {
+            for (_, binding) in m.scope(scope).all_bindings() {
+                let Binding::Assign(value) = binding else {
+                    continue;
+                };
+                if !matches!(value.as_ref(), Expr::Call(_)) {
+                    continue;
+                }
+                if let Value::Functional(functional) = self.resolve(module, scope, value) {
+                    if !found.contains(&functional) {
+                        found.push(functional);
+                    }
+                }
+            }
+        }
+        found.sort_by_key(|functional| functional.offset);
+        found
+    }
+
+    pub fn class_kind(&self, id: ClassId) -> ClassKind {
+        let class = self.class(id);
+        let is_typed = class.node.bases.iter().any(|base| {
+            matches!(
+                self.resolve(id.module, class.scope, base),
+                Value::Factory(Factory::Typing) | Value::PrivateNamedTuple
+            )
+        });
+        if is_typed {
+            ClassKind::TypedNamedTuple
+        } else {
+            ClassKind::Regular
+        }
+    }
+
+    fn resolve_expr(&self, module: ModuleId, scope: ScopeId, expr: &Expr, depth: usize) -> Value {
+        if depth > MAX_DEPTH {
+            return Value::Unknown;
+        }
+        match expr {
+            Expr::Name(ast::ExprName { id, .. }) => match self.module(module).lookup(scope, id) {
+                Some((scope, bindings)) => {
+                    self.resolve_bindings(module, scope, bindings, depth + 1)
+                }
+                None => match self.star_member(module, id, depth + 1) {
+                    Value::Unknown if BUILTIN_CLASSES.contains(&id.as_str()) => {
+                        Value::Builtin(id.to_string())
+                    }
+                    value => value,
+                },
+            },
+            Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+                match self.resolve_expr(module, scope, value, depth + 1) {
+                    Value::Module(name) => self.module_member(module, &name, attr, depth + 1),
+                    _ => Value::Unknown,
+                }
+            }
+            // `Pair[int]` is a generic alias of `Pair`; subclassing it makes a
+            // subclass of `Pair` itself.
+            Expr::Subscript(ast::ExprSubscript { value, .. }) => {
+                match self.resolve_expr(module, scope, value, depth + 1) {
+                    value @ (Value::Class(_) | Value::Generic) => value,
+                    _ => Value::Unknown,
+                }
+            }
+            Expr::Call(call) => {
+                let Value::Factory(factory) =
+                    self.resolve_expr(module, scope, &call.func, depth + 1)
+                else {
+                    return Value::Unknown;
+                };
+                let typename = call.args.first().or_else(|| {
+                    call.keywords
+                        .iter()
+                        .find(|keyword| {
+                            keyword
+                                .arg
+                                .as_ref()
+                                .is_some_and(|arg| arg.as_str() == "typename")
+                        })
+                        .map(|keyword| &keyword.value)
+                });
+                let Some(typename) = typename else {
+                    return Value::Unknown;
+                };
+                Value::Functional(FunctionalNamedTuple {
+                    factory,
+                    module,
+                    typename: match typename {
+                        Expr::Constant(ast::ExprConstant {
+                            value: ast::Constant::Str(typename),
+                            ..
+                        }) => typename.clone(),
+                        _ => "<unnamed>".to_owned(),
+                    },
+                    offset: call.start(),
+                    fields: signature::functional_fields(
+                        &self.module(module).source,
+                        factory,
+                        call,
+                    ),
+                })
+            }
+            _ => Value::Unknown,
+        }
+    }
+
+    /// Evaluates the first of several alternative bindings that resolves.
+    fn resolve_bindings(
+        &self,
+        module: ModuleId,
+        scope: ScopeId,
+        bindings: &[Binding],
+        depth: usize,
+    ) -> Value {
+        bindings
+            .iter()
+            .map(|binding| self.resolve_binding(module, scope, binding, depth))
+            .find(|value| *value != Value::Unknown)
+            .unwrap_or(Value::Unknown)
+    }
+
+    fn resolve_binding(
+        &self,
+        module: ModuleId,
+        scope: ScopeId,
+        binding: &Binding,
+        depth: usize,
+    ) -> Value {
+        match binding {
+            Binding::Module(name) => Value::Module(name.clone()),
+            Binding::ImportFrom { module: from, name } => {
+                self.module_member(module, from, name, depth)
+            }
+            Binding::Class(index) => Value::Class(ClassId {
+                module,
+                index: *index,
+            }),
+            Binding::Assign(value) => self.resolve_expr(module, scope, value, depth),
+            Binding::Opaque => Value::Unknown,
+        }
+    }
+
+    /// Evaluates `module.name` as imported by `importer`, where `module` may
+    /// or may not be part of the project.
+    fn module_member(&self, importer: ModuleId, module: &str, name: &str, depth: usize) -> Value {
+        if depth > MAX_DEPTH {
+            return Value::Unknown;
+        }
+        if let Some((value, _)) = known_member(module, name) {
+            return value.clone();
+        }
+        if let Some(id) = self.module_by_name(importer, module) {
+            // `from . import models` in `pkg/__init__.py` binds `models` to
+            // `pkg.models`, which is the submodule below, not the binding.
+            let value = self
+                .module(id)
+                .scope(ScopeId::MODULE)
+                .bindings(name)
+                .iter()
+                .filter(|binding| {
+                    !matches!(binding, Binding::ImportFrom { module: from, name: imported }
+                        if from == module && imported == name)
+                })
+                .map(|binding| self.resolve_binding(id, ScopeId::MODULE, binding, depth + 1))
+                .find(|value| *value != Value::Unknown)
+                .unwrap_or_else(|| self.star_member(id, name, depth + 1));
+            if value != Value::Unknown {
+                return value;
+            }
+        }
+        let submodule = format!("{module}.{name}");
+        if self.module_by_name(importer, &submodule).is_some() {
+            return Value::Module(submodule);
+        }
+        Value::Unknown
+    }
+
+    /// Evaluates a global `name` that `module` may have picked up through
+    /// `from ... import *`.
+    fn star_member(&self, module: ModuleId, name: &str, depth: usize) -> Value {
+        for star in &self.module(module).star_imports {
+            let exported = match self.module_by_name(module, star) {
+                Some(id) => self.module(id).exports(name),
+                // Third-party and standard library modules aren't parsed.
+                None => known_member(star, name).is_some_and(|(_, exported)| exported),
+            };
+            if exported {
+                let value = self.module_member(module, star, name, depth);
+                if value != Value::Unknown {
+                    return value;
+                }
+            }
+        }
+        Value::Unknown
+    }
+}
+
+/// Builtin classes that matter when combining bases, see [`crate::mro`].
+pub const BUILTIN_CLASSES: &[&str] = &[
+    "object",
+    "tuple",
+    "int",
+    "float",
+    "complex",
+    "str",
+    "bytes",
+    "bytearray",
+    "list",
+    "dict",
+    "set",
+    "frozenset",
+    "BaseException",
+    "Exception",
+    "ValueError",
+    "TypeError",
+    "KeyError",
+    "LookupError",
+    "RuntimeError",
+    "OSError",
+];
+
+/// The names outside of the project that resolution knows about: module,
+/// name, value, and whether `from module import *` exports it.
+const KNOWN_MEMBERS: &[(&str, &str, Value, bool)] = &[
+    (
+        "typing",
+        "NamedTuple",
+        Value::Factory(Factory::Typing),
+        true,
+    ),
+    ("typing", "_NamedTuple", Value::PrivateNamedTuple, false),
+    ("typing", "NamedTupleMeta", Value::NamedTupleMeta, false),
+    ("typing", "Generic", Value::Generic, true),
+    (
+        "typing_extensions",
+        "NamedTuple",
+        Value::Factory(Factory::Typing),
+        true,
+    ),
+    (
+        "typing_extensions",
+        "_NamedTupleMeta",
+        Value::NamedTupleMeta,
+        false,
+    ),
+    ("typing_extensions", "Generic", Value::Generic, true),
+    (
+        "collections",
+        "namedtuple",
+        Value::Factory(Factory::Collections),
+        true,
+    ),
+];
+
+fn known_member(module: &str, name: &str) -> Option<(&'static Value, bool)> {
+    KNOWN_MEMBERS
+        .iter()
+        .find(|(m, n, _, _)| *m == module && *n == name)
+        .map(|(_, _, value, exported)| (value, *exported))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A project of in-memory modules, given as name, whether it's a
+    /// package, and source. Names like `a.whl!pkg` are read from an archive.
+    pub(crate) fn project(modules: &[(&str, bool, &str)]) -> Project {
+        let mut project = Project::default();
+        for (spec, is_package, text) in modules {
+            let (archive, name) = spec.rsplit_once('!').unwrap_or(("", spec));
+            let path = ModulePath {
+                name: name.to_owned(),
+                is_package: *is_package,
+            };
+            let mut file = format!("{}.py", name.replace('.', "/"));
+            if !archive.is_empty() {
+                file = format!("{archive}!{file}");
+            }
+            let module = Module::parse(path, SourceFile::new(file, *text)).unwrap();
+            project.add_module(module);
+        }
+        project
+    }
+
+    fn base(project: &Project, module: &str, class: &str) -> Value {
+        let (archive, name) = module.rsplit_once('!').unwrap_or(("", module));
+        let (id, m) = project
+            .modules()
+            .find(|(_, m)| m.archive() == archive && m.name == name)
+            .unwrap();
+        let class = m.classes.iter().find(|c| c.name == class).unwrap();
+        project.resolve(id, class.scope, &class.node.bases[0])
+    }
+
+    fn class_named(project: &Project, name: &str) -> Value {
+        let (id, _) = project.classes().find(|(_, c)| c.name == name).unwrap();
+        Value::Class(id)
+    }
+
+    #[test]
+    fn submodule_imported_by_its_package() {
+        let project = project(&[
+            ("pkg", true, "from . import models\n"),
+            (
+                "pkg.models",
+                false,
+                "from typing import NamedTuple\nclass Foo(NamedTuple):\n    x: int\n",
+            ),
+            (
+                "app",
+                false,
+                "import pkg.models\nfrom pkg import models\nclass A(models.Foo): pass\nclass D(pkg.models.Foo): pass\n",
+            ),
+        ]);
+        let foo = class_named(&project, "Foo");
+        assert_eq!(base(&project, "app", "A"), foo);
+        assert_eq!(base(&project, "app", "D"), foo);
+    }
+
+    #[test]
+    fn package_reexport() {
+        let project = project(&[
+            ("pkg", true, "from .models import Foo as Bar\n"),
+            (
+                "pkg.models",
+                false,
+                "from typing import NamedTuple\nclass Foo(NamedTuple):\n    x: int\n",
+            ),
+            ("app", false, "from pkg import Bar\nclass A(Bar): pass\n"),
+        ]);
+        assert_eq!(base(&project, "app", "A"), class_named(&project, "Foo"));
+    }
+
+    #[test]
+    fn import_cycle() {
+        let project = project(&[
+            ("a", false, "from b import X\n"),
+            ("b", false, "from a import X\n"),
+            ("app", false, "from a import X\nclass A(X): pass\n"),
+        ]);
+        assert_eq!(base(&project, "app", "A"), Value::Unknown);
+    }
+
+    #[test]
+    fn archives_are_namespaces() {
+        let project = project(&[
+            (
+                "a.whl!pkg.models",
+                false,
+                "from typing import NamedTuple\nclass Foo(NamedTuple):\n    x: int\n",
+            ),
+            (
+                "a.whl!pkg.app",
+                false,
+                "from pkg.models import Foo\nclass A(Foo): pass\n",
+            ),
+            ("b.whl!pkg.models", false, "class Foo: pass\n"),
+            (
+                "b.whl!pkg.app",
+                false,
+                "from pkg.models import Foo\nclass A(Foo): pass\n",
+            ),
+            (
+                "c.whl!app",
+                false,
+                "from pkg.models import Foo\nclass A(Foo): pass\n",
+            ),
+        ]);
+        let (a, b) = match project
+            .classes()
+            .filter(|(_, c)| c.name == "Foo")
+            .collect::<Vec<_>>()[..]
+        {
+            [(a, _), (b, _)] => (Value::Class(a), Value::Class(b)),
+            _ => unreachable!(),
+        };
+        assert_eq!(base(&project, "a.whl!pkg.app", "A"), a);
+        assert_eq!(base(&project, "b.whl!pkg.app", "A"), b);
+        // Modules missing from an archive come from wherever they are first.
+        assert_eq!(base(&project, "c.whl!app", "A"), a);
+    }
+
+    #[test]
+    fn namedtuple_binding_forms() {
+        let project = project(&[(
+            "m",
+            false,
+            "import typing as t\n\
+             from typing_extensions import NamedTuple as ExtNT\n\
+             from typing import NamedTuple as NT\n\
+             from typing import *\n\
+             from typing import TYPE_CHECKING\n\
+             if TYPE_CHECKING:\n    from typing import NamedTuple as Checked\n\
+             else:\n    Checked = object\n\
+             try:\n    from typing_extensions import NamedTuple as Tried\n\
+             except ImportError:\n    from typing import NamedTuple as Tried\n\
+             class A(t.NamedTuple): pass\n\
+             class B(ExtNT): pass\n\
+             class C(NT): pass\n\
+             class D(NamedTuple): pass\n\
+             class E(Checked): pass\n\
+             class F(Tried): pass\n\
+             class G(t.Tuple): pass\n",
+        )]);
+        let kinds: Vec<(&str, ClassKind)> = project
+            .classes()
+            .map(|(id, class)| (class.name.as_str(), project.class_kind(id)))
+            .collect();
+        let typed = ClassKind::TypedNamedTuple;
+        assert_eq!(
+            kinds,
+            [
+                ("A", typed),
+                ("B", typed),
+                ("C", typed),
+                ("D", typed),
+                ("E", typed),
+                ("F", typed),
+                ("G", ClassKind::Regular),
+            ]
+        );
+    }
+
+    #[test]
+    fn star_imports_honour_all() {
+        let models = "from typing import NamedTuple\n\
+                      __all__ = [\"Point\", \"_Private\"]\n\
+                      class Point(NamedTuple):\n    x: int\n\
+                      class _Private(NamedTuple):\n    x: int\n\
+                      class Hidden(NamedTuple):\n    x: int\n";
+        let project = project(&[
+            ("models", false, models),
+            (
+                "app",
+                false,
+                "from models import *\n\
+                 class A(Point): pass\n\
+                 class B(_Private): pass\n\
+                 class C(Hidden): pass\n",
+            ),
+        ]);
+        assert_eq!(base(&project, "app", "A"), class_named(&project, "Point"));
+        assert_eq!(
+            base(&project, "app", "B"),
+            class_named(&project, "_Private")
+        );
+        assert_eq!(base(&project, "app", "C"), Value::Unknown);
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{self, Write};
+
+use crate::diagnostic::Diagnostic;
+
+const HEADER: &[&str] = &[
+    "path", "line", "column", "code", "rule", "message", "factory", "intents",
+];
+
+pub(super) fn write(diagnostics: &[Diagnostic], out: &mut dyn Write) -> io::Result<()> {
+    write_record(out, HEADER.iter().map(|field| field.to_string()))?;
+    for diagnostic in diagnostics {
+        let intents: Vec<_> = diagnostic
+            .intents
+            .iter()
+            .map(|intent| intent.as_str())
+            .collect();
+        write_record(
+            out,
+            [
+                diagnostic.path.clone(),
+                diagnostic.location.line.to_string(),
+                diagnostic.location.column.to_string(),
+                diagnostic.rule.code().to_owned(),
+                diagnostic.rule.name().to_owned(),
+                diagnostic.message.clone(),
+                diagnostic
+                    .factory
+                    .map(|factory| factory.as_str())
+                    .unwrap_or_default()
+                    .to_owned(),
+                intents.join(";"),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes one row, quoting fields as RFC 4180 requires.
+fn write_record(out: &mut dyn Write, fields: impl IntoIterator<Item = String>) -> io::Result<()> {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
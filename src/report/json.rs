@@ -0,0 +1,83 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::diagnostic::Diagnostic;
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    code: &'static str,
+    rule: &'static str,
+    path: &'a str,
+    line: usize,
+    column: usize,
+    message: &'a str,
+    factory: Option<&'static str>,
+    intents: Vec<&'static str>,
+    signatures: Option<JsonSignatures>,
+    notes: &'a [String],
+    runtime: Vec<JsonOutcome>,
+}
+
+#[derive(Serialize)]
+struct JsonOutcome {
+    versions: String,
+    outcome: &'static str,
+    detail: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct JsonSignatures {
+    runtime: String,
+    expected: String,
+}
+
+impl<'a> From<&'a Diagnostic> for JsonDiagnostic<'a> {
+    fn from(diagnostic: &'a Diagnostic) -> Self {
+        Self {
+            code: diagnostic.rule.code(),
+            rule: diagnostic.rule.name(),
+            path: &diagnostic.path,
+            line: diagnostic.location.line,
+            column: diagnostic.location.column,
+            message: &diagnostic.message,
+            factory: diagnostic.factory.map(|factory| factory.as_str()),
+            intents: diagnostic
+                .intents
+                .iter()
+                .map(|intent| intent.as_str())
+                .collect(),
+            signatures: diagnostic
+                .signatures
+                .as_ref()
+                .map(|signatures| JsonSignatures {
+                    runtime: signatures.runtime.to_string(),
+                    expected: signatures.expected.to_string(),
+                }),
+            notes: &diagnostic.notes,
+            runtime: diagnostic
+                .runtime
+                .iter()
+                .map(|outcome| JsonOutcome {
+                    versions: outcome.versions.to_string(),
+                    outcome: outcome.outcome.as_str(),
+                    detail: outcome.outcome.detail(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub(super) fn write(diagnostics: &[Diagnostic], out: &mut dyn Write) -> io::Result<()> {
+    let diagnostics: Vec<JsonDiagnostic> = diagnostics.iter().map(JsonDiagnostic::from).collect();
+    serde_json::to_writer_pretty(&mut *out, &diagnostics)?;
+    writeln!(out)
+}
+
+pub(super) fn write_lines(diagnostics: &[Diagnostic], out: &mut dyn Write) -> io::Result<()> {
+    for diagnostic in diagnostics {
+        serde_json::to_writer(&mut *out, &JsonDiagnostic::from(diagnostic))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
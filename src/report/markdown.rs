@@ -0,0 +1,30 @@
+use std::io::{self, Write};
+
+use crate::diagnostic::Diagnostic;
+
+pub(super) fn write(diagnostics: &[Diagnostic], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "| Location | Code | Message | Intent |")?;
+    writeln!(out, "| --- | --- | --- | --- |")?;
+    for diagnostic in diagnostics {
+        let intents: Vec<_> = diagnostic
+            .intents
+            .iter()
+            .map(|intent| format!("`{intent}`"))
+            .collect();
+        writeln!(
+            out,
+            "| `{}:{}` | {} | {} | {} |",
+            escape(&diagnostic.path),
+            diagnostic.location,
+            diagnostic.rule.code(),
+            escape(&diagnostic.message),
+            intents.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Keeps `text` inside its table cell.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
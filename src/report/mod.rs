@@ -0,0 +1,172 @@
+//! Writing diagnostics out in the supported formats.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::diagnostic::Diagnostic;
+
+mod csv;
+mod json;
+mod markdown;
+mod sarif;
+mod text;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// One `path:line:column: CODE message` line per diagnostic.
+    #[default]
+    Text,
+    /// A JSON array of diagnostic objects.
+    Json,
+    /// One JSON diagnostic object per line.
+    JsonLines,
+    /// A SARIF 2.1.0 log with rule metadata, for code scanning tools.
+    Sarif,
+    /// A CSV table with a header row; intent tags are separated by `;`.
+    Csv,
+    /// A Markdown table for pasting into issues.
+    Markdown,
+}
+
+impl Format {
+    pub const ALL: &'static [Format] = &[
+        Format::Text,
+        Format::Json,
+        Format::JsonLines,
+        Format::Sarif,
+        Format::Csv,
+        Format::Markdown,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Json => "json",
+            Format::JsonLines => "json-lines",
+            Format::Sarif => "sarif",
+            Format::Csv => "csv",
+            Format::Markdown => "markdown",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .iter()
+            .copied()
+            .find(|format| format.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Format::ALL.iter().map(|f| f.as_str()).collect();
+                format!(
+                    "unknown format `{s}`, expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Writes `diagnostics` in `format`. Paths are written as reported, except
+/// in SARIF logs, where they're made relative to `root`.
+pub fn write(
+    format: Format,
+    diagnostics: &[Diagnostic],
+    root: &Path,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    match format {
+        Format::Text => text::write(diagnostics, out),
+        Format::Json => json::write(diagnostics, out),
+        Format::JsonLines => json::write_lines(diagnostics, out),
+        Format::Sarif => sarif::write(diagnostics, root, out),
+        Format::Csv => csv::write(diagnostics, out),
+        Format::Markdown => markdown::write(diagnostics, out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intent::Intent;
+    use crate::project::Factory;
+    use crate::rules::Rule;
+    use crate::source::Location;
+
+    fn written(format: Format) -> String {
+        let diagnostic = Diagnostic {
+            factory: Some(Factory::Typing),
+            intents: vec![Intent::Methods, Intent::Properties],
+            ..Diagnostic::new(
+                Rule::NamedTupleSubclass,
+                "my pkg/a,b.py",
+                Location { line: 3, column: 7 },
+                "`Bar` inherits from \"Foo\" | really".to_owned(),
+            )
+        };
+        let mut out = Vec::new();
+        write(format, &[diagnostic], Path::new("/nonexistent"), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            written(Format::Csv),
+            "path,line,column,code,rule,message,factory,intents\r\n\
+             \"my pkg/a,b.py\",3,7,NTS001,namedtuple-subclass,\"`Bar` inherits from \"\"Foo\"\" | really\",typing.NamedTuple,methods;properties\r\n"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            written(Format::Markdown).lines().nth(2).unwrap(),
+            "| `my pkg/a,b.py:3:7` | NTS001 | `Bar` inherits from \"Foo\" \\| really | `methods`, `properties` |"
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let text = written(Format::JsonLines);
+        assert_eq!(text.lines().count(), 1);
+        let line: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(line["path"], "my pkg/a,b.py");
+        assert_eq!(line["column"], 7);
+        assert_eq!(
+            line["intents"],
+            serde_json::json!(["methods", "properties"])
+        );
+    }
+
+    #[test]
+    fn sarif_outside_the_root() {
+        let log: serde_json::Value = serde_json::from_str(&written(Format::Sarif)).unwrap();
+        let run = &log["runs"][0];
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        assert_eq!(
+            run["originalUriBaseIds"]["%SRCROOT%"]["uri"],
+            "file:///nonexistent/"
+        );
+        let result = &run["results"][0];
+        let artifact = &result["locations"][0]["physicalLocation"]["artifactLocation"];
+        assert_eq!(artifact["uri"], "my%20pkg/a%2Cb.py");
+        assert!(artifact.get("uriBaseId").is_none());
+        assert_eq!(
+            result["partialFingerprints"]["findingHash/v1"]
+                .as_str()
+                .unwrap()
+                .len(),
+            64
+        );
+    }
+}
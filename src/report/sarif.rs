@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::diagnostic::Diagnostic;
+use crate::discover;
+use crate::rules::Rule;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// What artifact URIs are relative to: the settings' root directory.
+const SRCROOT: &str = "%SRCROOT%";
+
+/// Versions the scheme of [`SarifResult::partial_fingerprints`].
+const FINGERPRINT: &str = "findingHash/v1";
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    original_uri_base_ids: BTreeMap<&'static str, ArtifactLocation>,
+    /// Columns are counted in characters, as in the other formats.
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: &'static str,
+    name: &'static str,
+    short_description: Message,
+    default_configuration: Configuration,
+}
+
+#[derive(Serialize)]
+struct Configuration {
+    enabled: bool,
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: [Location; 1],
+    /// Identifies the finding across runs without its line, so that code
+    /// scanning tools keep tracking it when code around it moves.
+    partial_fingerprints: BTreeMap<&'static str, String>,
+    properties: Properties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+}
+
+#[derive(Serialize)]
+struct Properties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    factory: Option<&'static str>,
+    intents: Vec<&'static str>,
+}
+
+/// Writes a log whose artifact URIs are relative to `root` where possible.
+pub(super) fn write(
+    diagnostics: &[Diagnostic],
+    root: &Path,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let rules = Rule::ALL
+        .iter()
+        .map(|rule| ReportingDescriptor {
+            id: rule.code(),
+            name: rule.name(),
+            short_description: Message {
+                text: rule.summary().to_owned(),
+            },
+            default_configuration: Configuration {
+                enabled: rule.is_default(),
+                level: "warning",
+            },
+        })
+        .collect();
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| result(diagnostic, root))
+        .collect();
+    let log = Log {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            original_uri_base_ids: BTreeMap::from([(
+                SRCROOT,
+                ArtifactLocation {
+                    uri: format!("{}/", file_uri(root)),
+                    uri_base_id: None,
+                },
+            )]),
+            column_kind: "unicodeCodePoints",
+            results,
+        }],
+    };
+    serde_json::to_writer_pretty(&mut *out, &log)?;
+    writeln!(out)
+}
+
+fn result(diagnostic: &Diagnostic, root: &Path) -> SarifResult {
+    // The message carries the signatures, runtime outcomes and notes too, as SARIF viewers
+    // show nothing else of a result.
+    let mut text = diagnostic.message.clone();
+    if let Some(signatures) = &diagnostic.signatures {
+        text.push('\n');
+        text.push_str(&signatures.to_string());
+    }
+    for outcome in &diagnostic.runtime {
+        text.push('\n');
+        text.push_str(&outcome.to_string());
+    }
+    for note in &diagnostic.notes {
+        text.push('\n');
+        text.push_str(note);
+    }
+    let artifact_location = match discover::relative_path(root, &diagnostic.path) {
+        Some(relative) => ArtifactLocation {
+            uri: percent_encode(&relative),
+            uri_base_id: Some(SRCROOT),
+        },
+        // Outside the root, or not found on disk.
+        None => ArtifactLocation {
+            uri: match Path::new(&diagnostic.path).canonicalize() {
+                Ok(path) => file_uri(&path),
+                Err(_) => percent_encode(&diagnostic.path),
+            },
+            uri_base_id: None,
+        },
+    };
+    let mut hasher = Sha256::new();
+    for part in [
+        diagnostic.rule.code(),
+        &artifact_location.uri,
+        &diagnostic.message,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    SarifResult {
+        rule_id: diagnostic.rule.code(),
+        rule_index: Rule::ALL
+            .iter()
+            .position(|rule| *rule == diagnostic.rule)
+            .unwrap_or_default(),
+        level: "warning",
+        message: Message { text },
+        locations: [Location {
+            physical_location: PhysicalLocation {
+                artifact_location,
+                region: Region {
+                    start_line: diagnostic.location.line,
+                    start_column: diagnostic.location.column,
+                },
+            },
+        }],
+        partial_fingerprints: BTreeMap::from([(FINGERPRINT, format!("{:x}", hasher.finalize()))]),
+        properties: Properties {
+            factory: diagnostic.factory.map(|factory| factory.as_str()),
+            intents: diagnostic
+                .intents
+                .iter()
+                .map(|intent| intent.as_str())
+                .collect(),
+        },
+    }
+}
+
+/// A `file:` URI for the absolute `path`.
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.trim_end_matches('/');
+    if path.starts_with('/') {
+        format!("file://{}", percent_encode(path))
+    } else {
+        format!("file:///{}", percent_encode(path))
+    }
+}
+
+/// Percent-encodes everything in `path` but unreserved characters, `/` and
+/// the `!` separating archive members.
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/!".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -0,0 +1,10 @@
+use std::io::{self, Write};
+
+use crate::diagnostic::Diagnostic;
+
+pub(super) fn write(diagnostics: &[Diagnostic], out: &mut dyn Write) -> io::Result<()> {
+    for diagnostic in diagnostics {
+        writeln!(out, "{diagnostic}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! The checks, each identified by a stable code.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::diagnostic::Diagnostic;
+use crate::project::Project;
+use crate::source::SourceFile;
+use crate::suppression;
+
+mod multiple_inheritance;
+mod namedtuple_metaclass;
+mod namedtuple_subclass;
+mod super_in_namedtuple;
+
+pub(crate) use namedtuple_subclass::typed_ancestor;
+pub(crate) use super_in_namedtuple::class_cell_usages;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    /// A class statement inherits from a typed named tuple.
+    NamedTupleSubclass,
+    /// A class statement inherits from a `collections.namedtuple` type.
+    ///
+    /// Off by default: subclassing `collections.namedtuple(...)` to add
+    /// methods is a long-standing, intentional idiom.
+    CollectionsNamedTupleSubclass,
+    /// Zero-argument `super()` or `__class__` in a method of a class that
+    /// inherits directly from `NamedTuple`.
+    SuperInNamedTuple,
+    /// `metaclass=NamedTupleMeta`, a metaclass derived from it, or a
+    /// `typing._NamedTuple` base.
+    NamedTupleMetaclass,
+    /// A named tuple combined with other bases in a way that fails at class
+    /// creation or silently drops fields or constructors.
+    NamedTupleMultipleInheritance,
+}
+
+impl Rule {
+    pub const ALL: &'static [Rule] = &[
+        Rule::NamedTupleSubclass,
+        Rule::CollectionsNamedTupleSubclass,
+        Rule::SuperInNamedTuple,
+        Rule::NamedTupleMetaclass,
+        Rule::NamedTupleMultipleInheritance,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Rule::NamedTupleSubclass => "NTS001",
+            Rule::CollectionsNamedTupleSubclass => "NTS002",
+            Rule::SuperInNamedTuple => "NTS003",
+            Rule::NamedTupleMetaclass => "NTS004",
+            Rule::NamedTupleMultipleInheritance => "NTS005",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::NamedTupleSubclass => "namedtuple-subclass",
+            Rule::CollectionsNamedTupleSubclass => "collections-namedtuple-subclass",
+            Rule::SuperInNamedTuple => "super-in-namedtuple",
+            Rule::NamedTupleMetaclass => "namedtuple-metaclass",
+            Rule::NamedTupleMultipleInheritance => "namedtuple-multiple-inheritance",
+        }
+    }
+
+    /// A one-sentence description, used as rule metadata in SARIF reports.
+    pub fn summary(self) -> &'static str {
+        match self {
+            Rule::NamedTupleSubclass => "Class inherits from a typed named tuple.",
+            Rule::CollectionsNamedTupleSubclass => {
+                "Class inherits from a `collections.namedtuple` type."
+            }
+            Rule::SuperInNamedTuple => {
+                "Zero-argument `super()` or `__class__` in a typed named tuple body."
+            }
+            Rule::NamedTupleMetaclass => {
+                "Class uses `NamedTupleMeta` or a `typing._NamedTuple` base directly."
+            }
+            Rule::NamedTupleMultipleInheritance => {
+                "Named tuple combined with other bases fails or loses fields."
+            }
+        }
+    }
+
+    /// Whether the rule runs without being selected explicitly.
+    pub fn is_default(self) -> bool {
+        match self {
+            Rule::NamedTupleSubclass
+            | Rule::SuperInNamedTuple
+            | Rule::NamedTupleMetaclass
+            | Rule::NamedTupleMultipleInheritance => true,
+            Rule::CollectionsNamedTupleSubclass => false,
+        }
+    }
+
+    pub fn defaults() -> Vec<Rule> {
+        Rule::ALL
+            .iter()
+            .copied()
+            .filter(|rule| rule.is_default())
+            .collect()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Accepts either the code (`NTS001`) or the name (`namedtuple-subclass`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.code().eq_ignore_ascii_case(s) || rule.name() == s)
+            .ok_or_else(|| format!("unknown rule `{s}`"))
+    }
+}
+
+/// Every NTS001 finding, including suppressed ones: whether a class statement
+/// fails at runtime doesn't depend on comments.
+pub(crate) fn namedtuple_subclasses(project: &Project) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    namedtuple_subclass::check(project, &mut diagnostics);
+    diagnostics.retain(|diagnostic| diagnostic.rule == Rule::NamedTupleSubclass);
+    diagnostics
+}
+
+/// Runs the `selected` rules over the project, returning diagnostics sorted by
+/// location. Diagnostics suppressed in the source, see [`crate::suppression`],
+/// are left out.
+pub fn check(project: &Project, selected: &[Rule]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    namedtuple_subclass::check(project, &mut diagnostics);
+    super_in_namedtuple::check(project, &mut diagnostics);
+    namedtuple_metaclass::check(project, &mut diagnostics);
+    multiple_inheritance::check(project, &mut diagnostics);
+    let sources: HashMap<&str, &SourceFile> = project
+        .modules()
+        .map(|(_, module)| (module.source.path(), &module.source))
+        .collect();
+    diagnostics.retain(|diagnostic| {
+        let source = sources[diagnostic.path.as_str()];
+        selected.contains(&diagnostic.rule)
+            && !suppression::skips_file(source)
+            && !suppression::is_suppressed(source, diagnostic.rule, diagnostic.location.line)
+    });
+    diagnostics.sort_by(|a, b| (&a.path, a.location, a.rule).cmp(&(&b.path, b.location, b.rule)));
+    diagnostics
+}
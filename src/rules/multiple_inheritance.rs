@@ -0,0 +1,266 @@
+//! NTS005: named tuples combined with other bases.
+//!
+//! ```python
+//! class Bar(Foo, Slotted):  # NTS005: layout conflict
+//!     ...
+//!
+//! class Baz(Mixin, Foo):  # NTS005: `Mixin.__new__` replaces `Foo`'s
+//!     ...
+//! ```
+//!
+//! The static MRO and instance layouts of the bases decide what happens.
+//! The class statement raises `TypeError` when the bases have no consistent
+//! MRO or when another base brings its own instance layout, like non-empty
+//! `__slots__` or a builtin such as `int`. Otherwise it succeeds but can
+//! silently lose fields or constructors: only the first named tuple in the
+//! MRO builds the instance, a mixin `__new__` ahead of it replaces it unless
+//! it calls `super().__new__`, a mixin `__init__` runs with the constructor's
+//! arguments wherever it is, and mixin annotations never become fields.
+
+use rustpython_parser::ast::{self, Expr, Ranged, Stmt};
+
+use crate::diagnostic::Diagnostic;
+use crate::mro::{self, Layout, MroEntry};
+use crate::project::{Factory, Project};
+use crate::rules::Rule;
+use crate::runtime::{Behaviour, Outcome};
+use crate::signature;
+use crate::visit::{self, Visitor};
+
+pub(super) fn check(project: &Project, diagnostics: &mut Vec<Diagnostic>) {
+    for (id, class) in project.classes() {
+        if class.node.bases.len() < 2 {
+            continue;
+        }
+        let module = project.module(id.module);
+        let bases: Vec<MroEntry> = class
+            .node
+            .bases
+            .iter()
+            .map(|base| MroEntry::from_base(project, id.module, class.scope, base))
+            .collect();
+        let Some(namedtuple) = bases.iter().position(|base| base.is_namedtuple(project)) else {
+            continue;
+        };
+        let namedtuple_name = bases[namedtuple].name(project);
+        let typed = bases.iter().any(|base| match base {
+            MroEntry::Class(_) => base.is_namedtuple(project),
+            MroEntry::Functional(functional) => functional.factory == Factory::Typing,
+            _ => false,
+        });
+        // Inheriting from a typed named tuple is what the proposal forbids.
+        let behaviour = |outcome| {
+            if typed {
+                Behaviour::Subclass {
+                    outcome,
+                    generic: None,
+                }
+            } else {
+                Behaviour::Always(outcome)
+            }
+        };
+        let report = |at: usize, message: String, notes: Vec<String>, outcome| Diagnostic {
+            notes,
+            behaviour: Some(behaviour(outcome)),
+            ..Diagnostic::new(
+                Rule::NamedTupleMultipleInheritance,
+                module.source.path(),
+                module.source.location(class.node.bases[at].start()),
+                message,
+            )
+        };
+
+        let conflict = bases.iter().enumerate().find_map(|(i, base)| {
+            let layout = mro::layout(project, base);
+            Layout::Tuple.conflicts_with(&layout).then_some((i, layout))
+        });
+        if let Some((at, layout)) = conflict {
+            let why = match layout {
+                Layout::Slots(owner) => format!(
+                    "`{}` declares non-empty `__slots__`",
+                    project.class(owner).qualname
+                ),
+                _ => format!("`{}` has its own instance layout", bases[at].name(project)),
+            };
+            diagnostics.push(report(
+                at,
+                format!(
+                    "`{}` combines named tuple `{namedtuple_name}` with `{}`; the class statement raises `TypeError: multiple bases have instance lay-out conflict`",
+                    class.qualname,
+                    bases[at].name(project),
+                ),
+                vec![format!("{why}, which can't be combined with `tuple`'s")],
+                Outcome::Raises("`TypeError: multiple bases have instance lay-out conflict`"),
+            ));
+            continue;
+        }
+
+        let mro = match mro::class_mro(project, id) {
+            Ok(mro) => mro,
+            Err(err) => {
+                diagnostics.push(report(
+                    0,
+                    format!(
+                        "`{}` combines named tuple `{namedtuple_name}` with other bases; the class statement raises `TypeError: {err}`",
+                        class.qualname
+                    ),
+                    Vec::new(),
+                    Outcome::Raises("`TypeError` from the MRO"),
+                ));
+                continue;
+            }
+        };
+        let losses = silent_losses(project, &mro);
+        if losses.is_empty() {
+            continue;
+        }
+        let mut notes = losses;
+        let names: Vec<String> = mro.iter().map(|entry| entry.name(project)).collect();
+        notes.push(format!("MRO: {}", names.join(", ")));
+        diagnostics.push(report(
+            namedtuple,
+            format!(
+                "`{}` combines named tuple `{namedtuple_name}` with other bases and silently loses fields or constructors",
+                class.qualname
+            ),
+            notes,
+            Outcome::Misbehaves("fields or constructors are lost"),
+        ));
+    }
+}
+
+/// What a successfully created class loses through its MRO, one note each.
+fn silent_losses(project: &Project, mro: &[MroEntry]) -> Vec<String> {
+    let mut losses = Vec::new();
+    let Some(builder) = mro[1..].iter().find(|entry| entry.is_namedtuple(project)) else {
+        return losses;
+    };
+    let builder_name = builder.name(project);
+    // Neither named tuples nor `tuple` define `__init__`, so the first one in
+    // the MRO runs wherever it is.
+    let init = mro.iter().enumerate().find_map(|(i, entry)| match entry {
+        MroEntry::Class(id) => method(&project.class(*id).node.body, "__init__").map(|_| (i, *id)),
+        _ => None,
+    });
+    if let Some((i, id)) = init.filter(|(i, _)| *i > 0 && !mro[*i].is_namedtuple(project)) {
+        let mut loss = format!(
+            "`{}.__init__` runs after `{builder_name}.__new__` with the same arguments",
+            project.class(id).qualname
+        );
+        if mro[..i].iter().all(|entry| entry != builder) {
+            loss.push_str(
+                ", and from Python 3.11 `inspect.signature` reports its parameters instead of the fields",
+            );
+        }
+        losses.push(loss);
+    }
+    let mut before_builder = true;
+    for entry in &mro[1..] {
+        if entry == builder {
+            before_builder = false;
+            continue;
+        }
+        if entry.is_namedtuple(project) {
+            let fields = match entry {
+                MroEntry::Class(id) => {
+                    let def = project.class(*id);
+                    let module = project.module(id.module);
+                    Some(signature::class_fields(&module.source, &def.node.body))
+                }
+                MroEntry::Functional(functional) => functional.fields.clone(),
+                _ => None,
+            };
+            let fields = fields
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .map(|field| format!("`{}`", field.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .filter(|fields| !fields.is_empty())
+                .map_or_else(
+                    || "all fields".to_owned(),
+                    |fields| format!("fields {fields}"),
+                );
+            losses.push(format!(
+                "{fields} of named tuple `{}` are dropped: only `{builder_name}.__new__` runs",
+                entry.name(project)
+            ));
+            continue;
+        }
+        let MroEntry::Class(id) = entry else {
+            continue;
+        };
+        let def = project.class(*id);
+        let name = &def.qualname;
+        if let Some(body) = method(&def.node.body, "__new__") {
+            if !before_builder {
+                losses.push(format!(
+                    "`{name}.__new__` is never called: `{builder_name}.__new__` comes first in the MRO and doesn't call `super().__new__`"
+                ));
+            } else if !calls_super_new(body) {
+                losses.push(format!(
+                    "`{name}.__new__` comes before `{builder_name}` in the MRO and replaces the named tuple constructor without calling `super().__new__`, so the fields are never set"
+                ));
+            }
+        }
+        let module = project.module(id.module);
+        let annotations = signature::class_fields(&module.source, &def.node.body);
+        if !annotations.is_empty() {
+            let names: Vec<String> = annotations
+                .iter()
+                .map(|field| format!("`{}`", field.name))
+                .collect();
+            losses.push(format!(
+                "annotations {} of `{name}` never become fields of the named tuple",
+                names.join(", ")
+            ));
+        }
+    }
+    losses
+}
+
+/// The body of the method `name` defined in a class body.
+fn method<'a>(body: &'a [Stmt], name: &str) -> Option<&'a [Stmt]> {
+    body.iter().find_map(|stmt| match stmt {
+        Stmt::FunctionDef(ast::StmtFunctionDef {
+            name: defined,
+            body,
+            ..
+        })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+            name: defined,
+            body,
+            ..
+        }) if defined.as_str() == name => Some(body.as_slice()),
+        _ => None,
+    })
+}
+
+/// Whether a `__new__` body calls `super().__new__` or
+/// `super(Class, cls).__new__`, passing construction on to the next class.
+fn calls_super_new(body: &[Stmt]) -> bool {
+    let mut finder = SuperNewFinder::default();
+    finder.visit_body(body);
+    finder.found
+}
+
+#[derive(Default)]
+struct SuperNewFinder {
+    found: bool,
+}
+
+impl<'a> Visitor<'a> for SuperNewFinder {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Attribute(ast::ExprAttribute { value, attr, .. }) = expr {
+            if attr.as_str() == "__new__"
+                && matches!(value.as_ref(), Expr::Call(ast::ExprCall { func, .. })
+                    if matches!(func.as_ref(), Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "super"))
+            {
+                self.found = true;
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
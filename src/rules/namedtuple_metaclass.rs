@@ -0,0 +1,304 @@
+//! NTS004: building classes with `NamedTupleMeta` directly.
+//!
+//! ```python
+//! class Bar(Foo, metaclass=NamedTupleMeta):  # NTS004
+//!     y: int
+//! ```
+//!
+//! `NamedTupleMeta.__new__` only accepts `NamedTuple` and `Generic` as bases
+//! (cpython#116241). Since Python 3.9, anything else fails the class
+//! statement, mostly with an `AssertionError`. With assertions disabled, 3.9
+//! and 3.10 build a named tuple from the class body alone instead, and 3.11
+//! and later raise a `TypeError`. The same goes for custom metaclasses
+//! deriving from `NamedTupleMeta` unless they override `__new__`.
+//!
+//! Other uses only appear to work: an explicit `metaclass=NamedTupleMeta`
+//! next to a `NamedTuple` base is redundant, since the resulting class's
+//! metaclass is `type` anyway, and `typing._NamedTuple` is a private name
+//! that doesn't exist before Python 3.9.
+
+use rustpython_parser::ast::{Expr, Ranged};
+
+use crate::diagnostic::Diagnostic;
+use crate::module::ClassDef;
+use crate::project::{ClassId, Factory, Project, Value};
+use crate::rules::Rule;
+use crate::runtime::{Behaviour, Outcome, PythonVersion, VersionRange};
+
+/// Guards the walk up a metaclass hierarchy against cycles.
+const MAX_DEPTH: usize = 32;
+
+/// The first version whose `NamedTupleMeta` checks its bases again after the
+/// skipped assertion, raising a `TypeError` under `python -O`.
+const CHECKED_WITHOUT_ASSERTIONS: PythonVersion = PythonVersion(11);
+
+/// What the class statement will do when it runs.
+enum Verdict {
+    /// It raises on newer versions; the notes say what happens with
+    /// assertions disabled instead.
+    Raises(Behaviour),
+    AppearsToWork(&'static str, Behaviour),
+    Undetermined(String),
+}
+
+impl Verdict {
+    fn behaviour(&self) -> Option<Behaviour> {
+        match self {
+            Verdict::Raises(behaviour) | Verdict::AppearsToWork(_, behaviour) => Some(*behaviour),
+            Verdict::Undetermined(_) => None,
+        }
+    }
+
+    /// Under `python -O`, `NamedTupleMeta`'s assertion is skipped: versions
+    /// before [`CHECKED_WITHOUT_ASSERTIONS`] go on to build a named tuple
+    /// from the class body alone, and later ones raise a `TypeError`.
+    fn notes(&self, class: &ClassDef) -> Vec<String> {
+        let Verdict::Raises(behaviour) = self else {
+            return Vec::new();
+        };
+        let error = match behaviour {
+            Behaviour::NamedTupleMetaWithoutBases => format!(
+                "TypeError: can only assign non-empty tuple to {}.__bases__, not ()",
+                class.name
+            ),
+            _ => "TypeError: can only inherit from a NamedTuple type and Generic".to_owned(),
+        };
+        let mut notes = Vec::new();
+        let since = raises_since(*behaviour);
+        if since < CHECKED_WITHOUT_ASSERTIONS {
+            let last = PythonVersion(CHECKED_WITHOUT_ASSERTIONS.0 - 1);
+            notes.push(format!(
+                "with assertions disabled, Python {} create the class instead, building a named tuple from the class body alone",
+                VersionRange { first: since, last }
+            ));
+        }
+        notes.push(format!(
+            "with assertions disabled, Python {CHECKED_WITHOUT_ASSERTIONS} and later raise `{error}` instead"
+        ));
+        notes
+    }
+}
+
+pub(super) fn check(project: &Project, diagnostics: &mut Vec<Diagnostic>) {
+    for (id, class) in project.classes() {
+        let module = project.module(id.module);
+        let bases: Vec<Value> = class
+            .node
+            .bases
+            .iter()
+            .map(|base| project.resolve(id.module, class.scope, base))
+            .collect();
+        let metaclass = class
+            .node
+            .keywords
+            .iter()
+            .find(|keyword| {
+                keyword
+                    .arg
+                    .as_ref()
+                    .is_some_and(|arg| arg.as_str() == "metaclass")
+            })
+            .map(|keyword| &keyword.value);
+        if let Some(metaclass) = metaclass {
+            let (name, overrides_new) = match project.resolve(id.module, class.scope, metaclass) {
+                Value::NamedTupleMeta => ("`NamedTupleMeta`".to_owned(), None),
+                Value::Class(meta) => match derived_meta(project, meta, 0) {
+                    Some(overrides_new) => (
+                        format!(
+                            "`{}` (derived from `NamedTupleMeta`)",
+                            project.class(meta).qualname
+                        ),
+                        overrides_new,
+                    ),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let verdict = match overrides_new {
+                Some(owner) => Verdict::Undetermined(format!(
+                    "`{owner}` overrides `__new__`, so whether class creation fails can't be determined statically"
+                )),
+                None => verdict(&bases),
+            };
+            diagnostics.push(Diagnostic {
+                behaviour: verdict.behaviour(),
+                notes: verdict.notes(class),
+                ..Diagnostic::new(
+                    Rule::NamedTupleMetaclass,
+                    module.source.path(),
+                    module.source.location(metaclass.start()),
+                    message(class, &format!("uses metaclass {name}"), &verdict),
+                )
+            });
+        } else if let Some(base) = class
+            .node
+            .bases
+            .iter()
+            .zip(&bases)
+            .find_map(|(base, value)| (*value == Value::PrivateNamedTuple).then_some(base))
+        {
+            diagnostics.push(private_base(class, base, project, id));
+        }
+    }
+}
+
+fn private_base(class: &ClassDef, base: &Expr, project: &Project, id: ClassId) -> Diagnostic {
+    let module = project.module(id.module);
+    Diagnostic {
+        behaviour: Some(Behaviour::PrivateNamedTupleBase),
+        ..Diagnostic::new(
+            Rule::NamedTupleMetaclass,
+            module.source.path(),
+            module.source.location(base.start()),
+            message(
+                class,
+                "inherits from the private `typing._NamedTuple`",
+                &Verdict::AppearsToWork(
+                    "it builds a typed named tuple, but the name is undocumented and missing before Python 3.9; use `typing.NamedTuple`",
+                    Behaviour::PrivateNamedTupleBase,
+                ),
+            ),
+        )
+    }
+}
+
+fn verdict(bases: &[Value]) -> Verdict {
+    if bases.is_empty() {
+        return Verdict::Raises(Behaviour::NamedTupleMetaWithoutBases);
+    }
+    let only_allowed = bases.iter().all(|base| {
+        matches!(
+            base,
+            Value::Factory(Factory::Typing) | Value::PrivateNamedTuple | Value::Generic
+        )
+    });
+    if only_allowed {
+        Verdict::AppearsToWork(
+            "the metaclass is redundant next to `NamedTuple`, and the class it builds has `type` as its metaclass anyway",
+            Behaviour::Always(Outcome::Works),
+        )
+    } else {
+        Verdict::Raises(Behaviour::NamedTupleMetaWithOtherBases)
+    }
+}
+
+fn message(class: &ClassDef, what: &str, verdict: &Verdict) -> String {
+    let name = &class.qualname;
+    match verdict {
+        Verdict::Raises(behaviour) => {
+            let since = raises_since(*behaviour);
+            format!("`{name}` {what}; the class statement raises on Python {since} and later")
+        }
+        Verdict::AppearsToWork(why, _) => {
+            format!("`{name}` {what}; this only appears to work: {why}")
+        }
+        Verdict::Undetermined(why) => format!("`{name}` {what}; {why}"),
+    }
+}
+
+/// The first version on which the class statement raises; older versions
+/// behave differently, see `Behaviour::outcome`.
+fn raises_since(behaviour: Behaviour) -> PythonVersion {
+    VersionRange::default()
+        .versions()
+        .find(|version| behaviour.outcome(*version).exception().is_some())
+        .unwrap_or(PythonVersion::OLDEST)
+}
+
+/// If `class` is a metaclass deriving from `NamedTupleMeta`, returns the
+/// qualified name of the first class in the chain overriding `__new__`, if any.
+fn derived_meta(project: &Project, class: ClassId, depth: usize) -> Option<Option<String>> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let def = project.class(class);
+    let own_new = def
+        .node
+        .body
+        .iter()
+        .any(|stmt| {
+            stmt.as_function_def_stmt()
+                .is_some_and(|f| f.name.as_str() == "__new__")
+        })
+        .then(|| def.qualname.clone());
+    def.node.bases.iter().find_map(
+        |base| match project.resolve(class.module, def.scope, base) {
+            Value::NamedTupleMeta => Some(own_new.clone()),
+            Value::Class(parent) => derived_meta(project, parent, depth + 1)
+                .map(|inherited| own_new.clone().or(inherited)),
+            _ => None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::project::tests::project;
+    use crate::rules::{self, Rule};
+    use crate::runtime::Behaviour;
+
+    #[test]
+    fn metaclass_uses() {
+        let project = project(&[(
+            "m",
+            false,
+            "import typing\n\
+             from typing import NamedTuple, NamedTupleMeta\n\
+             class Foo(NamedTuple):\n\
+             \x20   x: int\n\
+             class Bare(metaclass=NamedTupleMeta):\n\
+             \x20   x: int\n\
+             class Bar(Foo, metaclass=NamedTupleMeta):\n\
+             \x20   y: int\n\
+             class Redundant(NamedTuple, metaclass=NamedTupleMeta):\n\
+             \x20   x: int\n\
+             class Private(typing._NamedTuple):\n\
+             \x20   x: int\n\
+             class Meta(NamedTupleMeta):\n\
+             \x20   def __new__(cls, name, bases, ns):\n        return super().__new__(cls, name, bases, ns)\n\
+             class Derived(Meta):\n\
+             \x20   pass\n\
+             class Custom(Foo, metaclass=Derived):\n\
+             \x20   y: int\n",
+        )]);
+        let diagnostics = rules::check(&project, &[Rule::NamedTupleMetaclass]);
+        let lines: Vec<usize> = diagnostics.iter().map(|d| d.location.line).collect();
+        assert_eq!(lines, [5, 7, 9, 11, 18]);
+        assert_eq!(
+            diagnostics[0].message,
+            "`Bare` uses metaclass `NamedTupleMeta`; the class statement raises on Python 3.9 and later"
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            [
+                "with assertions disabled, Python 3.9..3.10 create the class instead, building a named tuple from the class body alone",
+                "with assertions disabled, Python 3.11 and later raise `TypeError: can only assign non-empty tuple to Bare.__bases__, not ()` instead",
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].behaviour,
+            Some(Behaviour::NamedTupleMetaWithoutBases)
+        );
+        assert_eq!(
+            diagnostics[1].notes[1],
+            "with assertions disabled, Python 3.11 and later raise `TypeError: can only inherit from a NamedTuple type and Generic` instead"
+        );
+        assert_eq!(
+            diagnostics[1].behaviour,
+            Some(Behaviour::NamedTupleMetaWithOtherBases)
+        );
+        assert!(diagnostics[2]
+            .message
+            .contains("this only appears to work: the metaclass is redundant"));
+        assert!(diagnostics[2].notes.is_empty());
+        assert_eq!(
+            diagnostics[3].message,
+            "`Private` inherits from the private `typing._NamedTuple`; this only appears to work: it builds a typed named tuple, but the name is undocumented and missing before Python 3.9; use `typing.NamedTuple`"
+        );
+        assert_eq!(
+            diagnostics[4].message,
+            "`Custom` uses metaclass `Derived` (derived from `NamedTupleMeta`); `Meta` overrides `__new__`, so whether class creation fails can't be determined statically"
+        );
+        assert_eq!(diagnostics[4].behaviour, None);
+    }
+}
@@ -0,0 +1,329 @@
+//! NTS001: inheriting from a typed named tuple.
+//!
+//! ```python
+//! class Foo(NamedTuple):
+//!     x: int
+//!
+//! class Bar(Foo):  # NTS001
+//!     y: int
+//! ```
+//!
+//! `Bar` keeps `Foo`'s constructor, silently ignores `y`, and has `type` as its
+//! metaclass rather than `NamedTupleMeta`. Typed named tuples should be treated
+//! as implicitly `@final`. Subclasses of `Bar` keep the same constructor and
+//! are reported too, naming the classes in between.
+//!
+//! The functional forms `NamedTuple("Foo", [("x", int)])` and
+//! `NamedTuple("Foo", x=int)` create the same kind of type, so inheriting from
+//! them is reported too, whether they are assigned to a name first or called
+//! inline in the bases.
+//!
+//! Generic typed named tuples, `class Pair(NamedTuple, Generic[T])` or
+//! `class Pair[T](NamedTuple)`, are followed through subscripted bases like
+//! `class IntPair(Pair[int])`.
+//!
+//! When the subclass declares annotated fields, like `y` above, the
+//! diagnostic shows the constructor it gets at runtime, `(x: int)`, next to
+//! the one dataclasses, attrs or pydantic would give it, `(x: int, y: int)`.
+//!
+//! In `.pyi` stubs, such as a typeshed checkout, the finding notes that the
+//! stub itself would need editing once typed named tuples are final.
+//!
+//! NTS002: inheriting from a `collections.namedtuple` type.
+//!
+//! ```python
+//! class Point(collections.namedtuple("Point", "x y")):  # NTS002
+//!     def norm(self): ...
+//! ```
+//!
+//! This is the documented way to add methods to an untyped named tuple, so
+//! it's only reported when selected.
+
+use std::collections::HashSet;
+
+use rustpython_parser::ast::{Expr, Ranged};
+
+use crate::diagnostic::Diagnostic;
+use crate::intent;
+use crate::project::{ClassId, ClassKind, Factory, FunctionalNamedTuple, ModuleId, Project, Value};
+use crate::rules::Rule;
+use crate::runtime::{Behaviour, GenericBase, Outcome};
+use crate::signature::{self, ConstructorSignatures, Signature};
+
+pub(super) fn check(project: &Project, diagnostics: &mut Vec<Diagnostic>) {
+    for (id, class) in project.classes() {
+        let module = project.module(id.module);
+        for base in &class.node.bases {
+            // A regular class in between passes the named tuple's
+            // constructor on unchanged, so its subclasses are reported too.
+            let (value, through, link) = match project.resolve(id.module, class.scope, base) {
+                Value::Class(base_id) if project.class_kind(base_id) == ClassKind::Regular => {
+                    match typed_ancestor(project, base_id, &mut HashSet::new()) {
+                        Some(mut ancestor) => {
+                            ancestor.through.insert(0, base_id);
+                            (ancestor.value, ancestor.through, ancestor.base)
+                        }
+                        None => continue,
+                    }
+                }
+                value => (value, Vec::new(), base),
+            };
+            let (factory, base_name, base_fields, generic) = match value {
+                Value::Class(base_id)
+                    if project.class_kind(base_id) == ClassKind::TypedNamedTuple =>
+                {
+                    let base_class = project.class(base_id);
+                    let base_module = project.module(base_id.module);
+                    (
+                        Factory::Typing,
+                        qualify(project, id.module, base_id.module, &base_class.qualname),
+                        Some(signature::class_fields(
+                            &base_module.source,
+                            &base_class.node.body,
+                        )),
+                        generic_base(project, base_id, link),
+                    )
+                }
+                Value::Functional(base) => (
+                    base.factory,
+                    qualify(project, id.module, base.module, &base.typename),
+                    base.fields,
+                    None,
+                ),
+                _ => continue,
+            };
+            // Annotations of the classes in between are ignored the same way.
+            let mut added = Vec::new();
+            for between in &through {
+                let between_class = project.class(*between);
+                let between_module = project.module(between.module);
+                added.extend(signature::class_fields(
+                    &between_module.source,
+                    &between_class.node.body,
+                ));
+            }
+            added.extend(signature::class_fields(&module.source, &class.node.body));
+            let signatures = match base_fields {
+                Some(fields) if !added.is_empty() => {
+                    Some(ConstructorSignatures::new(Signature { fields }, &added))
+                }
+                _ => None,
+            };
+            let via = match base {
+                Expr::Subscript(_) => format!(
+                    " through generic alias `{}`",
+                    &module.source.text()[base.range()]
+                ),
+                _ if !through.is_empty() => {
+                    let names: Vec<String> = through
+                        .iter()
+                        .map(|between| {
+                            let name = &project.class(*between).qualname;
+                            format!("`{}`", qualify(project, id.module, between.module, name))
+                        })
+                        .collect();
+                    format!(" through {}", names.join(", "))
+                }
+                _ => String::new(),
+            };
+            let (rule, message, behaviour) = match factory {
+                Factory::Typing => (
+                    Rule::NamedTupleSubclass,
+                    format!(
+                        "`{}` inherits from typed NamedTuple `{base_name}`{via} (created by `{factory}`); typed named tuples are implicitly final",
+                        class.qualname
+                    ),
+                    Behaviour::Subclass {
+                        outcome: if added.is_empty() {
+                            Outcome::Works
+                        } else {
+                            Outcome::Misbehaves("the declared fields are ignored")
+                        },
+                        generic,
+                    },
+                ),
+                Factory::Collections => (
+                    Rule::CollectionsNamedTupleSubclass,
+                    format!(
+                        "`{}` inherits from named tuple `{base_name}`{via} (created by `{factory}`)",
+                        class.qualname
+                    ),
+                    Behaviour::Always(Outcome::Works),
+                ),
+            };
+            let mut notes = Vec::new();
+            if module.is_stub() && rule == Rule::NamedTupleSubclass {
+                notes.push(
+                    "declared in a stub: the stub describes this class as it exists at runtime, so it needs editing if typed named tuples become final".to_owned(),
+                );
+            }
+            diagnostics.push(Diagnostic {
+                factory: Some(factory),
+                intents: intent::classify(&class.node.body),
+                signatures,
+                notes,
+                behaviour: Some(behaviour),
+                ..Diagnostic::new(
+                    rule,
+                    module.source.path(),
+                    module.source.location(base.start()),
+                    message,
+                )
+            });
+        }
+    }
+}
+
+/// How `id` is generic, as the base `base` of a subclass, if it is.
+fn generic_base(project: &Project, id: ClassId, base: &Expr) -> Option<GenericBase> {
+    let class = project.class(id);
+    let type_params = !class.node.type_params.is_empty();
+    let generic = type_params
+        || class
+            .node
+            .bases
+            .iter()
+            .any(|base| project.resolve(id.module, class.scope, base) == Value::Generic);
+    generic.then_some(GenericBase {
+        type_params,
+        subscripted: matches!(base, Expr::Subscript(_)),
+    })
+}
+
+/// A typed named tuple that a regular class inherits from through other
+/// regular classes.
+pub(crate) struct Ancestor<'a> {
+    /// The classes between the regular class and the named tuple.
+    pub(crate) through: Vec<ClassId>,
+    /// The base naming the named tuple in the last of them.
+    pub(crate) base: &'a Expr,
+    pub(crate) value: Value,
+}
+
+/// The first typed named tuple `id` inherits from, searching its bases
+/// depth first like the MRO mostly does, or `None` if there is none.
+pub(crate) fn typed_ancestor<'a>(
+    project: &'a Project,
+    id: ClassId,
+    seen: &mut HashSet<ClassId>,
+) -> Option<Ancestor<'a>> {
+    if !seen.insert(id) {
+        return None;
+    }
+    let class = project.class(id);
+    for base in &class.node.bases {
+        match project.resolve(id.module, class.scope, base) {
+            Value::Class(base_id) if project.class_kind(base_id) == ClassKind::TypedNamedTuple => {
+                return Some(Ancestor {
+                    through: Vec::new(),
+                    base,
+                    value: Value::Class(base_id),
+                });
+            }
+            value @ Value::Functional(FunctionalNamedTuple {
+                factory: Factory::Typing,
+                ..
+            }) => {
+                return Some(Ancestor {
+                    through: Vec::new(),
+                    base,
+                    value,
+                });
+            }
+            Value::Class(base_id) => {
+                if let Some(mut ancestor) = typed_ancestor(project, base_id, seen) {
+                    ancestor.through.insert(0, base_id);
+                    return Some(ancestor);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Prefixes `name` with its module when it's defined outside of `from`.
+fn qualify(project: &Project, from: ModuleId, module: ModuleId, name: &str) -> String {
+    if module == from {
+        name.to_owned()
+    } else {
+        format!("{}.{name}", project.module(module).name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::project::tests::project;
+    use crate::rules::{self, Rule};
+
+    #[test]
+    fn indirect_subclasses() {
+        let project = project(&[(
+            "m",
+            false,
+            "from typing import NamedTuple\n\
+             class Point2D(NamedTuple):\n    x: int\n    y: int\n\
+             class Point3D(Point2D):\n    z: int = 0\n\
+             class Point4D(Point3D):\n    w: int = 0\n",
+        )]);
+        let diagnostics = rules::check(&project, &[Rule::NamedTupleSubclass]);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1]
+            .starts_with("`Point4D` inherits from typed NamedTuple `Point2D` through `Point3D` "));
+        let signatures = diagnostics[1].signatures.as_ref().unwrap();
+        assert_eq!(signatures.runtime.to_string(), "(x: int, y: int)");
+        assert_eq!(
+            signatures.expected.to_string(),
+            "(x: int, y: int, z: int = 0, w: int = 0)"
+        );
+    }
+
+    #[test]
+    fn functional_forms() {
+        let project = project(&[(
+            "m",
+            false,
+            "import typing\n\
+             from typing import NamedTuple\n\
+             Listed = NamedTuple(\"Listed\", [(\"x\", int), (\"y\", str)])\n\
+             Keywords = typing.NamedTuple(\"Keywords\", x=int)\n\
+             class A(Listed):\n    z: int\n\
+             class B(Keywords): pass\n\
+             class C(NamedTuple(\"Inline\", [(\"x\", int)])): pass\n\
+             class D(NamedTuple(typename=\"Named\", fields=[])): pass\n",
+        )]);
+        let diagnostics = rules::check(&project, &[Rule::NamedTupleSubclass]);
+        let bases: Vec<&str> = diagnostics
+            .iter()
+            .map(|d| d.message.split('`').nth(3).unwrap())
+            .collect();
+        assert_eq!(bases, ["Listed", "Keywords", "Inline", "Named"]);
+        let signatures = diagnostics[0].signatures.as_ref().unwrap();
+        assert_eq!(signatures.runtime.to_string(), "(x: int, y: str)");
+        assert_eq!(signatures.expected.to_string(), "(x: int, y: str, z: int)");
+    }
+
+    #[test]
+    fn collections_namedtuple_subclasses_are_opt_in() {
+        let project = project(&[(
+            "m",
+            false,
+            "import collections\n\
+             from collections import namedtuple as nt\n\
+             Base = collections.namedtuple(\"Base\", \"x y\")\n\
+             class A(Base):\n    def norm(self): ...\n\
+             class B(nt(\"Inline\", [\"x\"])): pass\n",
+        )]);
+        assert!(rules::check(&project, &Rule::defaults()).is_empty());
+        let diagnostics = rules::check(&project, &[Rule::CollectionsNamedTupleSubclass]);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "`A` inherits from named tuple `Base` (created by `collections.namedtuple`)",
+                "`B` inherits from named tuple `Inline` (created by `collections.namedtuple`)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! NTS003: zero-argument `super()` in a typed named tuple.
+//!
+//! ```python
+//! class Foo(NamedTuple):
+//!     def bar(self) -> None:
+//!         super()  # NTS003
+//! ```
+//!
+//! Zero-argument `super()` and `__class__` make the compiler give the class
+//! body a `__class__` cell, which `NamedTupleMeta` doesn't pass on to the
+//! class it actually creates (cpython#85795). Since Python 3.8 the class
+//! statement itself fails, with a `RuntimeError` and, from Python 3.14, a
+//! `TypeError`; the headline takes both from [`Behaviour::SuperInNamedTuple`]. A subclass of `Foo` is an ordinary class, so the
+//! same code works there, which is one of the inconsistencies that make
+//! subclassing typed named tuples confusing.
+//!
+//! Nested functions and lambdas inside methods are searched too, since they
+//! share the method's `__class__` cell. Nested classes have their own.
+
+use rustpython_parser::ast::{self, Expr, Ranged, Stmt};
+
+use crate::diagnostic::Diagnostic;
+use crate::module::ClassDef;
+use crate::project::{ClassKind, ModuleId, Project};
+use crate::rules::Rule;
+use crate::runtime::{Behaviour, PythonVersion, VersionRange};
+use crate::visit::{self, Visitor};
+
+pub(super) fn check(project: &Project, diagnostics: &mut Vec<Diagnostic>) {
+    for (id, class) in project.classes() {
+        if project.class_kind(id) != ClassKind::TypedNamedTuple {
+            continue;
+        }
+        let module = project.module(id.module);
+        for usage in class_cell_usages(&class.node.body) {
+            let what = match usage {
+                Expr::Name(_) => "`__class__`",
+                _ => "zero-argument `super()`",
+            };
+            diagnostics.push(Diagnostic {
+                behaviour: Some(Behaviour::SuperInNamedTuple),
+                notes: runtime_error(project, id.module, class)
+                    .into_iter()
+                    .collect(),
+                ..Diagnostic::new(
+                    Rule::SuperInNamedTuple,
+                    module.source.path(),
+                    module.source.location(usage.start()),
+                    format!(
+                        "{what} in a method of typed NamedTuple `{}`; creating the class raises {}",
+                        class.qualname,
+                        raised(),
+                    ),
+                )
+            });
+        }
+    }
+}
+
+/// The zero-argument `super()` calls and `__class__` references in a class
+/// body that need a `__class__` cell.
+pub(crate) fn class_cell_usages(body: &[Stmt]) -> Vec<&Expr> {
+    let mut finder = ClassCellFinder::default();
+    finder.visit_body(body);
+    finder.usages
+}
+
+/// The exceptions the class statement raises, with the versions raising
+/// each, from the behaviour table.
+fn raised() -> String {
+    let raised: Vec<String> = Behaviour::SuperInNamedTuple
+        .outcomes(VersionRange::default())
+        .iter()
+        .filter_map(|outcome| {
+            let exception = outcome.outcome.exception()?;
+            Some(format!("`{exception}` on Python {}", outcome.versions))
+        })
+        .collect();
+    raised.join(" and ")
+}
+
+/// How CPython words the `RuntimeError`, on the versions that raise it.
+fn runtime_error(project: &Project, module: ModuleId, class: &ClassDef) -> Option<String> {
+    let versions: Vec<PythonVersion> = VersionRange::default()
+        .versions()
+        .filter(|version| {
+            Behaviour::SuperInNamedTuple.outcome(*version).exception() == Some("RuntimeError")
+        })
+        .collect();
+    let (first, last) = (versions.first()?, versions.last()?);
+    Some(format!(
+        "on Python {} the error reads `RuntimeError: __class__ not set defining '{}' as <class '{}.{}'>. Was __classcell__ propagated to type.__new__?`",
+        VersionRange {
+            first: *first,
+            last: *last,
+        },
+        class.name,
+        project.module(module).name,
+        class.qualname,
+    ))
+}
+
+/// Collects the expressions in a class body that need a `__class__` cell.
+#[derive(Default)]
+struct ClassCellFinder<'a> {
+    /// How many functions or lambdas enclose the current node.
+    function_depth: usize,
+    usages: Vec<&'a Expr>,
+}
+
+impl<'a> Visitor<'a> for ClassCellFinder<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
+            }) => {
+                for decorator in decorator_list {
+                    self.visit_expr(decorator);
+                }
+                visit::walk_arguments(self, args);
+                if let Some(returns) = returns {
+                    self.visit_expr(returns);
+                }
+                self.function_depth += 1;
+                self.visit_body(body);
+                self.function_depth -= 1;
+            }
+            // Only the parts of a nested class evaluated in the enclosing scope.
+            Stmt::ClassDef(ast::StmtClassDef {
+                bases,
+                keywords,
+                decorator_list,
+                ..
+            }) => {
+                for expr in decorator_list
+                    .iter()
+                    .chain(bases)
+                    .chain(keywords.iter().map(|keyword| &keyword.value))
+                {
+                    self.visit_expr(expr);
+                }
+            }
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Lambda(ast::ExprLambda { args, body, .. }) => {
+                visit::walk_arguments(self, args);
+                self.function_depth += 1;
+                self.visit_expr(body);
+                self.function_depth -= 1;
+            }
+            Expr::Call(ast::ExprCall {
+                func,
+                args,
+                keywords,
+                ..
+            }) if self.function_depth > 0
+                && args.is_empty()
+                && keywords.is_empty()
+                && matches!(func.as_ref(), Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "super") =>
+            {
+                self.usages.push(expr);
+            }
+            Expr::Name(ast::ExprName { id, .. })
+                if self.function_depth > 0 && id.as_str() == "__class__" =>
+            {
+                self.usages.push(expr);
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::project::tests::project;
+    use crate::rules::{self, Rule};
+
+    #[test]
+    fn class_cell_usages_in_direct_namedtuples() {
+        let project = project(&[(
+            "m",
+            false,
+            "from typing import NamedTuple\n\
+             class Foo(NamedTuple):\n\
+             \x20   x: int\n\
+             \x20   def a(self):\n        return super().a()\n\
+             \x20   def b(self):\n        return lambda: __class__\n\
+             \x20   def c(self):\n        return super(Foo, self).c()\n\
+             \x20   class Inner:\n        def d(self):\n            return super().d()\n\
+             class Bar(Foo):\n\
+             \x20   def e(self):\n        return super().e()\n",
+        )]);
+        let diagnostics = rules::check(&project, &[Rule::SuperInNamedTuple]);
+        let lines: Vec<usize> = diagnostics.iter().map(|d| d.location.line).collect();
+        assert_eq!(lines, [5, 7]);
+        assert_eq!(
+            diagnostics[0].message,
+            "zero-argument `super()` in a method of typed NamedTuple `Foo`; creating the class raises `RuntimeError` on Python 3.8..3.13 and `TypeError` on Python 3.14..3.15"
+        );
+        assert!(diagnostics[1]
+            .message
+            .starts_with("`__class__` in a method"));
+        assert_eq!(
+            diagnostics[0].notes,
+            ["on Python 3.8..3.13 the error reads `RuntimeError: __class__ not set defining 'Foo' as <class 'm.Foo'>. Was __classcell__ propagated to type.__new__?`"]
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! How reported class statements behave at runtime across CPython versions.
+//!
+//! | Pattern | 3.8 | 3.9–3.10 | 3.11 | 3.12–3.13 | 3.14 | 3.15 |
+//! | --- | --- | --- | --- | --- | --- | --- |
+//! | subclass declaring fields | misbehaves | misbehaves | misbehaves | misbehaves | misbehaves | raises (proposed) |
+//! | subclass adding methods only | works | works | works | works | works | raises (proposed) |
+//! | subclass of a `Generic` named tuple, as `Pair` | as above | raises | as above | as above | as above | raises (proposed) |
+//! | subclass of a `Generic` named tuple, as `Pair[int]` | raises | raises | as above | as above | as above | raises (proposed) |
+//! | subclass of a `class Box[T](NamedTuple)` | raises | raises | raises | as above | as above | raises (proposed) |
+//! | `super()` or `__class__` in the body | raises | raises | raises | raises | raises | raises |
+//! | `metaclass=NamedTupleMeta` without bases | works | raises | raises | raises | raises | raises |
+//! | `metaclass=NamedTupleMeta` with other bases | misbehaves | raises | raises | raises | raises | raises |
+//! | `typing._NamedTuple` base | raises | works | works | works | works | works |
+//!
+//! Python 3.8 drops `Generic` from a named tuple's bases, so the generic
+//! named tuple itself works but can't be subscripted; 3.9 and 3.10 reject
+//! it.
+//!
+//! 3.15 is where the README's proposal, a `TypeError` for class statements
+//! inheriting from typed named tuples, could land at the earliest; its
+//! outcomes say "(proposed)".
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A CPython 3 minor version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PythonVersion(pub u8);
+
+impl PythonVersion {
+    pub const OLDEST: PythonVersion = PythonVersion(8);
+    pub const NEWEST: PythonVersion = PythonVersion(15);
+    /// The first version the proposed `TypeError` could be in.
+    pub const PROPOSED_FINAL: PythonVersion = PythonVersion(15);
+}
+
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "3.{}", self.0)
+    }
+}
+
+impl FromStr for PythonVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s
+            .strip_prefix("3.")
+            .and_then(|minor| minor.parse().ok())
+            .map(PythonVersion)
+            .filter(|version| (PythonVersion::OLDEST..=PythonVersion::NEWEST).contains(version));
+        version.ok_or_else(|| {
+            format!(
+                "unknown Python version `{s}`, expected a version from {} to {}",
+                PythonVersion::OLDEST,
+                PythonVersion::NEWEST
+            )
+        })
+    }
+}
+
+/// An inclusive range of versions, written `3.8..3.15` or `3.12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    pub first: PythonVersion,
+    pub last: PythonVersion,
+}
+
+impl VersionRange {
+    pub fn versions(self) -> impl Iterator<Item = PythonVersion> {
+        (self.first.0..=self.last.0).map(PythonVersion)
+    }
+}
+
+impl Default for VersionRange {
+    fn default() -> Self {
+        VersionRange {
+            first: PythonVersion::OLDEST,
+            last: PythonVersion::NEWEST,
+        }
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}..{}", self.first, self.last)
+        }
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, last) = s.split_once("..").unwrap_or((s, s));
+        let range = VersionRange {
+            first: first.parse()?,
+            last: last.parse()?,
+        };
+        if range.first > range.last {
+            return Err(format!("empty version range `{s}`"));
+        }
+        Ok(range)
+    }
+}
+
+/// What a class statement does when it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The class statement fails, with the error given.
+    Raises(&'static str),
+    /// The class is created but doesn't do what it looks like it does.
+    Misbehaves(&'static str),
+    Works,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Raises(_) => "raises",
+            Outcome::Misbehaves(_) => "misbehaves",
+            Outcome::Works => "works",
+        }
+    }
+
+    pub fn detail(self) -> Option<&'static str> {
+        match self {
+            Outcome::Raises(detail) | Outcome::Misbehaves(detail) => Some(detail),
+            Outcome::Works => None,
+        }
+    }
+
+    /// The name of the exception raised, e.g. `RuntimeError` for
+    /// ``Raises("`RuntimeError: ...`")``.
+    pub fn exception(self) -> Option<&'static str> {
+        let Outcome::Raises(detail) = self else {
+            return None;
+        };
+        let name = detail.strip_prefix('`')?;
+        let end = name.find(['`', ':']).unwrap_or(name.len());
+        Some(&name[..end])
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Raises(detail) => write!(f, "raises {detail}"),
+            Outcome::Misbehaves(detail) => write!(f, "misbehaves: {detail}"),
+            Outcome::Works => f.write_str("works"),
+        }
+    }
+}
+
+/// Which row of the behaviour table a diagnostic falls under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behaviour {
+    /// A class statement inheriting from a typed named tuple.
+    Subclass {
+        /// What happens until the proposal, e.g. fields being ignored.
+        outcome: Outcome,
+        /// How the base is generic, if it is.
+        generic: Option<GenericBase>,
+    },
+    /// Zero-argument `super()` or `__class__` in a typed named tuple body.
+    SuperInNamedTuple,
+    /// `metaclass=NamedTupleMeta` on a class without bases.
+    NamedTupleMetaWithoutBases,
+    /// `metaclass=NamedTupleMeta` next to bases other than `NamedTuple` and
+    /// `Generic`.
+    NamedTupleMetaWithOtherBases,
+    /// A `typing._NamedTuple` base.
+    PrivateNamedTupleBase,
+    /// The same outcome in every version.
+    Always(Outcome),
+}
+
+/// A generic typed named tuple base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenericBase {
+    /// Declared with PEP 695 type parameters, `class Box[T](NamedTuple)`,
+    /// rather than with a `Generic[T]` base.
+    pub type_params: bool,
+    /// Subclassed through a generic alias like `Box[int]`.
+    pub subscripted: bool,
+}
+
+impl GenericBase {
+    /// What goes wrong before the subclass's own class statement, if
+    /// anything.
+    fn outcome(self, version: PythonVersion) -> Option<Outcome> {
+        match version.0 {
+            ..=11 if self.type_params => Some(Outcome::Raises(
+                "`SyntaxError` at the base's type parameters",
+            )),
+            ..=8 if self.subscripted => Some(Outcome::Raises(
+                "`TypeError: 'type' object is not subscriptable` at the base",
+            )),
+            9..=10 if !self.type_params => Some(Outcome::Raises(
+                "`TypeError: Multiple inheritance with NamedTuple is not supported` at the base",
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Behaviour {
+    pub fn outcome(self, version: PythonVersion) -> Outcome {
+        if let Behaviour::Subclass {
+            generic: Some(generic),
+            ..
+        } = self
+        {
+            if let Some(outcome) = generic.outcome(version) {
+                return outcome;
+            }
+        }
+        match self {
+            _ if self.is_proposed(version) => {
+                Outcome::Raises("`TypeError: can't inherit from typed named tuples` (proposed)")
+            }
+            Behaviour::Subclass { outcome, .. } => outcome,
+            Behaviour::SuperInNamedTuple if version < PythonVersion(14) => {
+                Outcome::Raises("`RuntimeError: __class__ not set defining ...`")
+            }
+            Behaviour::SuperInNamedTuple => Outcome::Raises(
+                "`TypeError`: `super()` and `__class__` are unsupported in named tuple methods",
+            ),
+            Behaviour::NamedTupleMetaWithoutBases => match version.0 {
+                ..=8 => Outcome::Works,
+                9..=10 => Outcome::Raises("`IndexError: tuple index out of range`"),
+                _ => Outcome::Raises("`AssertionError`"),
+            },
+            Behaviour::NamedTupleMetaWithOtherBases if version < PythonVersion(9) => {
+                Outcome::Misbehaves(
+                    "a new named tuple is built from the class body alone, dropping the bases",
+                )
+            }
+            Behaviour::NamedTupleMetaWithOtherBases => Outcome::Raises("`AssertionError`"),
+            Behaviour::PrivateNamedTupleBase if version < PythonVersion(9) => {
+                Outcome::Raises("`AttributeError: module 'typing' has no attribute '_NamedTuple'`")
+            }
+            Behaviour::PrivateNamedTupleBase => Outcome::Works,
+            Behaviour::Always(outcome) => outcome,
+        }
+    }
+
+    /// Whether the outcome on `version` is the README's proposal rather than
+    /// what CPython does.
+    pub fn is_proposed(self, version: PythonVersion) -> bool {
+        matches!(self, Behaviour::Subclass { .. }) && version >= PythonVersion::PROPOSED_FINAL
+    }
+
+    /// The outcomes over `range`, with consecutive versions that behave the
+    /// same merged.
+    pub fn outcomes(self, range: VersionRange) -> Vec<VersionOutcome> {
+        let mut outcomes: Vec<VersionOutcome> = Vec::new();
+        for version in range.versions() {
+            let outcome = self.outcome(version);
+            match outcomes.last_mut() {
+                Some(last) if last.outcome == outcome => last.versions.last = version,
+                _ => outcomes.push(VersionOutcome {
+                    versions: VersionRange {
+                        first: version,
+                        last: version,
+                    },
+                    outcome,
+                }),
+            }
+        }
+        outcomes
+    }
+}
+
+/// What happens on a range of versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionOutcome {
+    pub versions: VersionRange,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for VersionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Python {}: {}", self.versions, self.outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subclass(generic: Option<GenericBase>) -> Behaviour {
+        Behaviour::Subclass {
+            outcome: Outcome::Works,
+            generic,
+        }
+    }
+
+    #[test]
+    fn version_ranges() {
+        assert_eq!(
+            "3.9..3.12".parse(),
+            Ok(VersionRange {
+                first: PythonVersion(9),
+                last: PythonVersion(12),
+            })
+        );
+        assert_eq!("3.12".parse::<VersionRange>().unwrap().to_string(), "3.12");
+        assert!("3.12..3.9".parse::<VersionRange>().is_err());
+        assert!("3.7".parse::<PythonVersion>().is_err());
+        assert!("2.7".parse::<PythonVersion>().is_err());
+    }
+
+    #[test]
+    fn generic_bases_by_version() {
+        let unsubscripted = subclass(Some(GenericBase {
+            type_params: false,
+            subscripted: false,
+        }));
+        let subscripted = subclass(Some(GenericBase {
+            type_params: false,
+            subscripted: true,
+        }));
+        assert_eq!(unsubscripted.outcome(PythonVersion(8)), Outcome::Works);
+        assert!(subscripted.outcome(PythonVersion(8)).exception().is_some());
+        for behaviour in [unsubscripted, subscripted] {
+            assert!(behaviour.outcome(PythonVersion(9)).exception().is_some());
+            assert!(behaviour.outcome(PythonVersion(10)).exception().is_some());
+            assert_eq!(behaviour.outcome(PythonVersion(11)), Outcome::Works);
+        }
+    }
+
+    #[test]
+    fn type_params_need_python_3_12() {
+        let behaviour = subclass(Some(GenericBase {
+            type_params: true,
+            subscripted: true,
+        }));
+        let outcomes = behaviour.outcomes(VersionRange::default());
+        let ranges: Vec<String> = outcomes
+            .iter()
+            .map(|outcome| outcome.versions.to_string())
+            .collect();
+        assert_eq!(ranges, ["3.8..3.11", "3.12..3.14", "3.15"]);
+        assert!(behaviour.is_proposed(PythonVersion(15)));
+    }
+
+    #[test]
+    fn metaclass_without_bases() {
+        let behaviour = Behaviour::NamedTupleMetaWithoutBases;
+        assert_eq!(behaviour.outcome(PythonVersion(8)), Outcome::Works);
+        assert!(behaviour.outcome(PythonVersion(13)).exception().is_some());
+        assert!(!behaviour.is_proposed(PythonVersion(15)));
+    }
+}
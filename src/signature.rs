@@ -0,0 +1,290 @@
+//! Constructor signatures of named tuples, computed from their definitions.
+//!
+//! A subclass of a named tuple inherits `__new__` unchanged, so its runtime
+//! `inspect.signature` is the base's. Field-inheriting class builders like
+//! dataclasses, attrs and pydantic would instead append the subclass's
+//! annotated names. Showing both makes the silently ignored fields obvious.
+
+use std::fmt;
+
+use rustpython_parser::ast::{self, Expr, Ranged, Stmt};
+
+use crate::project::Factory;
+use crate::source::SourceFile;
+
+/// A constructor parameter, with its annotation and default as written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Field {
+    pub name: String,
+    pub annotation: Option<String>,
+    pub default: Option<String>,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, ": {annotation}")?;
+        }
+        match (&self.annotation, &self.default) {
+            (Some(_), Some(default)) => write!(f, " = {default}"),
+            (None, Some(default)) => write!(f, "={default}"),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+/// The parameter list `inspect.signature` would show for a class.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Signature {
+    pub fields: Vec<Field>,
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{field}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Signature {
+    /// The signature a dataclass-style subclass adding `added` would get:
+    /// new fields are appended, redeclared ones keep their position but take
+    /// the new annotation and default.
+    pub fn extend(&self, added: &[Field]) -> Signature {
+        let mut fields = self.fields.clone();
+        for field in added {
+            match fields
+                .iter_mut()
+                .find(|existing| existing.name == field.name)
+            {
+                Some(existing) => *existing = field.clone(),
+                None => fields.push(field.clone()),
+            }
+        }
+        Signature { fields }
+    }
+
+    /// The first field without a default that follows one with a default,
+    /// which dataclasses and attrs reject.
+    pub fn misordered_field(&self) -> Option<&Field> {
+        let mut seen_default = false;
+        self.fields.iter().find(|field| {
+            seen_default |= field.default.is_some();
+            seen_default && field.default.is_none()
+        })
+    }
+}
+
+/// The runtime signature of a field-adding subclass next to the one it would
+/// have if its annotations were inherited like dataclass fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructorSignatures {
+    pub runtime: Signature,
+    pub expected: Signature,
+}
+
+impl ConstructorSignatures {
+    pub fn new(base: Signature, added: &[Field]) -> Self {
+        let expected = base.extend(added);
+        Self {
+            runtime: base,
+            expected,
+        }
+    }
+}
+
+impl fmt::Display for ConstructorSignatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  runtime signature:  {}", self.runtime)?;
+        write!(f, "  expected signature: {}", self.expected)?;
+        if let Some(field) = self.expected.misordered_field() {
+            write!(
+                f,
+                " (dataclasses and attrs would reject non-default field `{}` after a default)",
+                field.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The annotated names in a class body, in order. `ClassVar` annotations
+/// declare class attributes, not fields, and are left out.
+pub fn class_fields(source: &SourceFile, body: &[Stmt]) -> Vec<Field> {
+    body.iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                annotation,
+                value,
+                ..
+            }) if !is_classvar(annotation) => match target.as_ref() {
+                Expr::Name(ast::ExprName { id, .. }) => Some(Field {
+                    name: id.to_string(),
+                    annotation: Some(source.slice(annotation.range()).to_owned()),
+                    default: value
+                        .as_ref()
+                        .map(|value| source.slice(value.range()).to_owned()),
+                }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether an annotation is `ClassVar`, `ClassVar[...]` or either of them
+/// through a module, like `typing.ClassVar[int]`, also as a string.
+pub fn is_classvar(annotation: &Expr) -> bool {
+    match annotation {
+        Expr::Subscript(ast::ExprSubscript { value, .. }) => is_classvar(value),
+        Expr::Name(ast::ExprName { id, .. }) => id.as_str() == "ClassVar",
+        Expr::Attribute(ast::ExprAttribute { attr, .. }) => attr.as_str() == "ClassVar",
+        Expr::Constant(ast::ExprConstant {
+            value: ast::Constant::Str(text),
+            ..
+        }) => {
+            let name = text.trim().split('[').next().unwrap_or_default().trim_end();
+            name.rsplit('.').next() == Some("ClassVar")
+        }
+        _ => false,
+    }
+}
+
+/// The fields of `NamedTuple("Foo", [("x", int)])`, `NamedTuple("Foo", x=int)`
+/// or `namedtuple("Foo", "x y", defaults=...)`, or `None` if they aren't
+/// literals.
+pub fn functional_fields(
+    source: &SourceFile,
+    factory: Factory,
+    call: &ast::ExprCall,
+) -> Option<Vec<Field>> {
+    let keyword = |name: &str| {
+        call.keywords
+            .iter()
+            .find(|keyword| keyword.arg.as_ref().is_some_and(|arg| arg.as_str() == name))
+            .map(|keyword| &keyword.value)
+    };
+    let text = |expr: &Expr| source.slice(expr.range()).to_owned();
+    match factory {
+        Factory::Typing => match call.args.get(1).or_else(|| keyword("fields")) {
+            Some(
+                Expr::List(ast::ExprList { elts, .. }) | Expr::Tuple(ast::ExprTuple { elts, .. }),
+            ) => elts
+                .iter()
+                .map(|elt| match elt {
+                    Expr::Tuple(ast::ExprTuple { elts, .. })
+                    | Expr::List(ast::ExprList { elts, .. }) => match elts.as_slice() {
+                        [name, annotation] => Some(Field {
+                            name: string_literal(name)?,
+                            annotation: Some(text(annotation)),
+                            default: None,
+                        }),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect(),
+            Some(_) => None,
+            None => Some(
+                call.keywords
+                    .iter()
+                    .filter_map(|keyword| {
+                        let name = keyword.arg.as_ref()?;
+                        (name.as_str() != "typename").then(|| Field {
+                            name: name.to_string(),
+                            annotation: Some(text(&keyword.value)),
+                            default: None,
+                        })
+                    })
+                    .collect(),
+            ),
+        },
+        Factory::Collections => {
+            let names: Vec<String> = match call.args.get(1).or_else(|| keyword("field_names"))? {
+                Expr::List(ast::ExprList { elts, .. })
+                | Expr::Tuple(ast::ExprTuple { elts, .. }) => {
+                    elts.iter().map(string_literal).collect::<Option<_>>()?
+                }
+                names => string_literal(names)?
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+            };
+            let defaults: Vec<String> = match keyword("defaults") {
+                Some(
+                    Expr::List(ast::ExprList { elts, .. })
+                    | Expr::Tuple(ast::ExprTuple { elts, .. }),
+                ) => elts.iter().map(text).collect(),
+                Some(Expr::Constant(ast::ExprConstant {
+                    value: ast::Constant::None,
+                    ..
+                }))
+                | None => Vec::new(),
+                Some(_) => return None,
+            };
+            let first_default = names.len().checked_sub(defaults.len())?;
+            Some(
+                names
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, name)| Field {
+                        name,
+                        annotation: None,
+                        default: i.checked_sub(first_default).map(|i| defaults[i].clone()),
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Constant(ast::ExprConstant {
+            value: ast::Constant::Str(value),
+            ..
+        }) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::Parse;
+
+    fn fields(text: &str) -> Vec<String> {
+        let source = SourceFile::new("m.py", text);
+        let suite = ast::Suite::parse(source.text(), source.path()).unwrap();
+        let [Stmt::ClassDef(class)] = suite.as_slice() else {
+            panic!("expected one class");
+        };
+        class_fields(&source, &class.body)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn class_fields_leave_out_classvars() {
+        let text = "class A:\n    x: int\n    a: ClassVar[int] = 1\n    b: typing.ClassVar = 2\n    c: 'ClassVar[str]' = ''\n    y: str = ''\n";
+        assert_eq!(fields(text), ["x: int", "y: str = ''"]);
+    }
+
+    #[test]
+    fn class_fields_keep_defaults_as_written() {
+        assert_eq!(
+            fields("class A:\n    x: list[int] = []\n    y = 1\n"),
+            ["x: list[int] = []"]
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//! What would break if inheriting from a typed named tuple raised
+//! `TypeError`, as the README proposes.
+//!
+//! Every class statement NTS001 reports is taken to raise when it runs. A
+//! module fails to import when such a statement runs at import time, that is
+//! outside of functions and `if TYPE_CHECKING:` blocks, or when it imports a
+//! failing module at import time. Importing `a.b.c` runs `a` and `a.b` first,
+//! so a failing package takes its submodules with it. Stubs are never
+//! imported and are left out.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use rustpython_parser::ast::{self, Expr, Stmt, TextSize};
+use serde::Serialize;
+
+use crate::imports::{self, ModulePath};
+use crate::module::Module;
+use crate::project::{ModuleId, ModuleNames, Project};
+use crate::rules;
+use crate::source::Location;
+
+#[derive(Debug, Serialize)]
+pub struct Simulation {
+    /// One entry per top-level package or module, sorted by name.
+    pub packages: Vec<Package>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Package {
+    pub name: String,
+    pub modules: usize,
+    /// How many of `modules` are tests, see [`is_test`].
+    pub test_modules: usize,
+    /// The modules that fail to import, sorted by name.
+    pub failing: Vec<Failure>,
+    /// Subclasses inside functions, which only fail when the function runs.
+    pub deferred: Vec<Subclass>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Failure {
+    pub module: String,
+    pub path: String,
+    pub is_test: bool,
+    pub cause: Cause,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    /// The module runs a failing class statement itself.
+    Defines(Subclass),
+    /// The module imports a failing module, the first one found on the
+    /// shortest chain back to a class statement.
+    Imports(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Subclass {
+    pub qualname: String,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Subclass {
+    pub fn location(&self) -> Location {
+        Location {
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+impl Package {
+    pub fn failing_tests(&self) -> usize {
+        self.failing
+            .iter()
+            .filter(|failure| failure.is_test)
+            .count()
+    }
+}
+
+impl Simulation {
+    pub fn failing(&self) -> usize {
+        self.packages
+            .iter()
+            .map(|package| package.failing.len())
+            .sum()
+    }
+
+    pub fn modules(&self) -> usize {
+        self.packages.iter().map(|package| package.modules).sum()
+    }
+}
+
+pub fn simulate(project: &Project) -> Simulation {
+    let runtime: Vec<(ModuleId, &Module)> = project
+        .modules()
+        .filter(|(_, module)| !module.is_stub())
+        .collect();
+    let mut by_name = ModuleNames::default();
+    for (id, module) in &runtime {
+        by_name.insert(*id, module, |_| false);
+    }
+
+    let mut executed: HashMap<ModuleId, ImportTime> = HashMap::new();
+    for (id, module) in &runtime {
+        let mut visitor = ImportTime::default();
+        visitor.visit_body(&module.body);
+        executed.insert(*id, visitor);
+    }
+
+    // Direct failures, and the subclasses that only fail later.
+    let mut causes: HashMap<ModuleId, Cause> = HashMap::new();
+    let mut deferred: HashMap<ModuleId, Vec<Subclass>> = HashMap::new();
+    for diagnostic in rules::namedtuple_subclasses(project) {
+        let Some(&(id, module)) = runtime
+            .iter()
+            .find(|(_, module)| module.source.path() == diagnostic.path)
+        else {
+            continue;
+        };
+        let Some(class) = module.innermost_class(diagnostic.location) else {
+            continue;
+        };
+        let subclass = Subclass {
+            qualname: class.qualname.clone(),
+            path: diagnostic.path.clone(),
+            line: diagnostic.location.line,
+            column: diagnostic.location.column,
+        };
+        if executed[&id].classes.contains(&class.node.range.start()) {
+            causes.entry(id).or_insert(Cause::Defines(subclass));
+        } else {
+            deferred.entry(id).or_default().push(subclass);
+        }
+    }
+
+    // Who imports whom at import time, reversed.
+    let mut importers: HashMap<ModuleId, Vec<ModuleId>> = HashMap::new();
+    for (id, module) in &runtime {
+        let path = ModulePath {
+            name: module.name.clone(),
+            is_package: module.is_package,
+        };
+        let mut dependencies = ancestors(&module.name);
+        dependencies.pop();
+        for import in &executed[id].imports {
+            dependencies.extend(import.modules(&path, module.archive(), &by_name));
+        }
+        for dependency in dependencies {
+            if let Some(dependency) = by_name.get(module.archive(), &dependency) {
+                if dependency != *id {
+                    importers.entry(dependency).or_default().push(*id);
+                }
+            }
+        }
+    }
+
+    let mut queue: VecDeque<ModuleId> = causes.keys().copied().collect();
+    queue.make_contiguous().sort();
+    while let Some(failed) = queue.pop_front() {
+        for &importer in importers.get(&failed).into_iter().flatten() {
+            if let Entry::Vacant(entry) = causes.entry(importer) {
+                entry.insert(Cause::Imports(project.module(failed).name.clone()));
+                queue.push_back(importer);
+            }
+        }
+    }
+
+    let mut packages: BTreeMap<&str, Package> = BTreeMap::new();
+    for (id, module) in &runtime {
+        let top = module.name.split('.').next().unwrap_or_default();
+        let package = packages.entry(top).or_insert_with(|| Package {
+            name: top.to_owned(),
+            modules: 0,
+            test_modules: 0,
+            failing: Vec::new(),
+            deferred: Vec::new(),
+        });
+        let is_test = is_test(&module.name);
+        package.modules += 1;
+        package.test_modules += usize::from(is_test);
+        if let Some(cause) = causes.remove(id) {
+            package.failing.push(Failure {
+                module: module.name.clone(),
+                path: module.source.path().to_owned(),
+                is_test,
+                cause,
+            });
+        }
+        package
+            .deferred
+            .extend(deferred.remove(id).into_iter().flatten());
+    }
+    let mut packages: Vec<Package> = packages.into_values().collect();
+    for package in &mut packages {
+        package.failing.sort_by(|a, b| a.module.cmp(&b.module));
+    }
+    Simulation { packages }
+}
+
+/// Test modules and their helpers, going by the names pytest and unittest
+/// discover: a `test` or `tests` package, `test_*` and `*_test` modules and
+/// `conftest`.
+pub fn is_test(module: &str) -> bool {
+    module.split('.').any(|part| {
+        matches!(part, "test" | "tests" | "conftest")
+            || part.starts_with("test_")
+            || part.ends_with("_test")
+    })
+}
+
+/// `a`, `a.b` and `a.b.c` for `a.b.c`.
+fn ancestors(name: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut current = String::new();
+    for part in name.split('.') {
+        if !current.is_empty() {
+            current.push('.');
+        }
+        current.push_str(part);
+        names.push(current.clone());
+    }
+    names
+}
+
+#[derive(Debug)]
+enum Import<'a> {
+    /// `import a.b`.
+    Module(&'a str),
+    /// `from <level dots><module> import <names>`.
+    From {
+        level: u32,
+        module: Option<&'a str>,
+        names: Vec<&'a str>,
+    },
+}
+
+impl Import<'_> {
+    /// The modules the statement runs, if they're in the project.
+    fn modules(&self, importer: &ModulePath, archive: &str, by_name: &ModuleNames) -> Vec<String> {
+        match self {
+            Import::Module(name) => ancestors(name),
+            Import::From {
+                level,
+                module,
+                names,
+            } => {
+                let Some(base) = imports::resolve_relative(importer, *level, *module) else {
+                    return Vec::new();
+                };
+                let mut modules = ancestors(&base);
+                // `from a import b` imports the submodule `a.b` if there is one.
+                modules.extend(
+                    names
+                        .iter()
+                        .map(|name| format!("{base}.{name}"))
+                        .filter(|name| by_name.get(archive, name).is_some()),
+                );
+                modules
+            }
+        }
+    }
+}
+
+/// The class statements and imports a module runs when it's imported.
+#[derive(Debug, Default)]
+struct ImportTime<'a> {
+    /// Class statements, by where they start.
+    classes: Vec<TextSize>,
+    imports: Vec<Import<'a>>,
+}
+
+impl<'a> ImportTime<'a> {
+    fn visit_body(&mut self, body: &'a [Stmt]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::ClassDef(class) => {
+                self.classes.push(class.range.start());
+                self.visit_body(&class.body);
+            }
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                self.imports.extend(
+                    names
+                        .iter()
+                        .map(|alias| Import::Module(alias.name.as_str())),
+                );
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module,
+                names,
+                level,
+                ..
+            }) => self.imports.push(Import::From {
+                level: level.map_or(0, |level| level.to_u32()),
+                module: module.as_ref().map(|module| module.as_str()),
+                names: names.iter().map(|alias| alias.name.as_str()).collect(),
+            }),
+            Stmt::If(ast::StmtIf {
+                test, body, orelse, ..
+            }) => {
+                if !is_type_checking(test) {
+                    self.visit_body(body);
+                }
+                self.visit_body(orelse);
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                self.visit_body(body);
+                for ast::ExceptHandler::ExceptHandler(handler) in handlers {
+                    self.visit_body(&handler.body);
+                }
+                self.visit_body(orelse);
+                self.visit_body(finalbody);
+            }
+            Stmt::For(ast::StmtFor { body, orelse, .. })
+            | Stmt::AsyncFor(ast::StmtAsyncFor { body, orelse, .. })
+            | Stmt::While(ast::StmtWhile { body, orelse, .. }) => {
+                self.visit_body(body);
+                self.visit_body(orelse);
+            }
+            Stmt::With(ast::StmtWith { body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { body, .. }) => {
+                self.visit_body(body);
+            }
+            Stmt::Match(ast::StmtMatch { cases, .. }) => {
+                for case in cases {
+                    self.visit_body(&case.body);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `TYPE_CHECKING` or `typing.TYPE_CHECKING`, whatever module it came from.
+fn is_type_checking(test: &Expr) -> bool {
+    match test {
+        Expr::Name(ast::ExprName { id, .. }) => id.as_str() == "TYPE_CHECKING",
+        Expr::Attribute(ast::ExprAttribute { attr, .. }) => attr.as_str() == "TYPE_CHECKING",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::tests::project;
+
+    #[test]
+    fn failures_follow_import_time_imports() {
+        let project = project(&[
+            ("pkg", true, ""),
+            (
+                "pkg.models",
+                false,
+                "from typing import NamedTuple\n\
+                 class Foo(NamedTuple):\n\
+                 \x20   x: int\n\
+                 class Bar(Foo): pass\n",
+            ),
+            ("pkg.api", false, "from pkg.models import Foo\n"),
+            ("pkg.app", false, "from . import api\n"),
+            (
+                "pkg.lazy",
+                false,
+                "def make():\n\
+                 \x20   from pkg.models import Foo\n\
+                 \x20   class Baz(Foo): pass\n",
+            ),
+            (
+                "pkg.typed",
+                false,
+                "from typing import TYPE_CHECKING\n\
+                 if TYPE_CHECKING:\n\
+                 \x20   from pkg.models import Foo\n",
+            ),
+            ("tests.test_api", false, "import pkg.api\n"),
+            ("other", false, "import json\n"),
+        ]);
+        let simulation = simulate(&project);
+        let names: Vec<&str> = simulation
+            .packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect();
+        assert_eq!(names, ["other", "pkg", "tests"]);
+        assert_eq!(simulation.modules(), 8);
+        assert_eq!(simulation.failing(), 4);
+
+        let pkg = &simulation.packages[1];
+        let failing: Vec<(&str, String)> = pkg
+            .failing
+            .iter()
+            .map(|failure| {
+                let cause = match &failure.cause {
+                    Cause::Defines(subclass) => format!("defines {}", subclass.qualname),
+                    Cause::Imports(module) => format!("imports {module}"),
+                };
+                (failure.module.as_str(), cause)
+            })
+            .collect();
+        assert_eq!(
+            failing,
+            [
+                ("pkg.api", "imports pkg.models".to_owned()),
+                ("pkg.app", "imports pkg.api".to_owned()),
+                ("pkg.models", "defines Bar".to_owned()),
+            ]
+        );
+        let deferred: Vec<&str> = pkg.deferred.iter().map(|s| s.qualname.as_str()).collect();
+        assert_eq!(deferred, ["make.<locals>.Baz"]);
+
+        let tests = &simulation.packages[2];
+        assert_eq!((tests.test_modules, tests.failing_tests()), (1, 1));
+    }
+
+    #[test]
+    fn test_module_names() {
+        for name in [
+            "tests.helpers",
+            "pkg.test",
+            "test_models",
+            "models_test",
+            "conftest",
+        ] {
+            assert!(is_test(name), "{name}");
+        }
+        for name in ["pkg.testing", "contest", "pkg.latest"] {
+            assert!(!is_test(name), "{name}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! `# noqa` comments and file-level skips.
+//!
+//! ```python
+//! class Bar(Foo):  # noqa: NTS001
+//!     ...
+//! ```
+//!
+//! A `# noqa` comment on the line a diagnostic is reported at suppresses the
+//! rules it lists by code, or every rule if it lists none. Codes never
+//! change between releases, so suppressions keep working. A comment line
+//! reading `# check-namedtuple: skip-file` anywhere in a file suppresses
+//! everything in it.
+
+use crate::rules::Rule;
+use crate::source::SourceFile;
+
+const SKIP_FILE: &str = "check-namedtuple: skip-file";
+
+/// Whether the file opts out of checking altogether.
+pub fn skips_file(source: &SourceFile) -> bool {
+    source.text().lines().any(|line| {
+        line.trim_start()
+            .strip_prefix('#')
+            .is_some_and(|comment| comment.trim() == SKIP_FILE)
+    })
+}
+
+/// Whether a `# noqa` comment on the one-based `line` covers `rule`.
+pub fn is_suppressed(source: &SourceFile, rule: Rule, line: usize) -> bool {
+    let line = source.line(line);
+    line.match_indices('#')
+        .any(|(index, _)| match noqa_codes(&line[index + 1..]) {
+            Some(codes) => {
+                codes.is_empty()
+                    || codes
+                        .iter()
+                        .any(|code| code.eq_ignore_ascii_case(rule.code()))
+            }
+            None => false,
+        })
+}
+
+/// The codes listed by a `noqa` comment, empty for a bare `# noqa`, or `None`
+/// if `comment` isn't one.
+fn noqa_codes(comment: &str) -> Option<Vec<&str>> {
+    let comment = comment.trim_start();
+    let rest = comment
+        .get(..4)?
+        .eq_ignore_ascii_case("noqa")
+        .then(|| &comment[4..])?;
+    let Some(list) = rest.trim_start().strip_prefix(':') else {
+        return (rest.is_empty() || rest.starts_with(char::is_whitespace)).then(Vec::new);
+    };
+    Some(
+        list.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|code| !code.is_empty())
+            .take_while(|code| is_code(code))
+            .collect(),
+    )
+}
+
+/// Whether `text` looks like a rule code: letters followed by digits.
+fn is_code(text: &str) -> bool {
+    let digits = text.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    digits.len() < text.len() && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suppressed(line: &str, rule: Rule) -> bool {
+        is_suppressed(&SourceFile::new("m.py", line), rule, 1)
+    }
+
+    #[test]
+    fn noqa_with_codes() {
+        let line = "class Bar(Foo):  # noqa: NTS001, NTS003";
+        assert!(suppressed(line, Rule::NamedTupleSubclass));
+        assert!(suppressed(line, Rule::SuperInNamedTuple));
+        assert!(!suppressed(line, Rule::NamedTupleMultipleInheritance));
+        assert!(suppressed(
+            "class Bar(Foo):  # NOQA:nts001",
+            Rule::NamedTupleSubclass
+        ));
+    }
+
+    #[test]
+    fn bare_noqa() {
+        assert!(suppressed(
+            "class Bar(Foo):  # noqa",
+            Rule::NamedTupleMetaclass
+        ));
+        assert!(suppressed(
+            "class Bar(Foo):  # type: ignore # noqa",
+            Rule::NamedTupleSubclass
+        ));
+        assert!(!suppressed(
+            "class Bar(Foo):  # noqanything",
+            Rule::NamedTupleSubclass
+        ));
+        assert!(!suppressed(
+            "class Bar(Foo):  # see noqa",
+            Rule::NamedTupleSubclass
+        ));
+    }
+
+    #[test]
+    fn codes_end_at_prose() {
+        let line = "class Bar(Foo):  # noqa: NTS001 subclassing on purpose, NTS005";
+        assert!(suppressed(line, Rule::NamedTupleSubclass));
+        assert!(!suppressed(line, Rule::NamedTupleMultipleInheritance));
+    }
+
+    #[test]
+    fn skip_file() {
+        let skipped = SourceFile::new("m.py", "import x\n  # check-namedtuple: skip-file\n");
+        assert!(skips_file(&skipped));
+        let kept = SourceFile::new("m.py", "x = '# check-namedtuple: skip-file'\n");
+        assert!(!skips_file(&kept));
+    }
+}
@@ -0,0 +1,865 @@
+//! Checking the checker's runtime predictions against real interpreters.
+//!
+//! Each finding with a [`Behaviour`] is turned into a minimal reproducer
+//! built from the parsed class statement: its fields, type parameters, and
+//! bases and metaclass as written, preceded by the classes and named tuple
+//! types those bases resolve to, written the same way. For NTS001 and NTS002
+//! only the bases leading to the named tuple are kept. Annotations are kept
+//! as unevaluated strings and defaults that aren't literals become `None`,
+//! so the reproducer runs anywhere. Methods and the rest of the module are
+//! left out. The reproducer is run in every local `python3.x` and compared
+//! with the prediction: whether the class statement raises and with which
+//! exception, and the parameter names `inspect.signature` reports for the
+//! class and which of them have defaults. Any difference is a bug in the
+//! checker.
+//!
+//! Reproducers run in isolated mode (`-I`) without `site`, in an empty
+//! temporary directory with an empty environment, and are killed after a
+//! timeout.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use rustpython_parser::ast::{self, Expr, Ranged};
+use rustpython_parser::text_size::TextRange;
+use rustpython_parser::Parse;
+use serde::Deserialize;
+
+use crate::diagnostic::Diagnostic;
+use crate::project::{ClassId, ClassKind, Factory, FunctionalNamedTuple, Project, Value};
+use crate::rules::{self, Rule};
+use crate::runtime::{Behaviour, Outcome, PythonVersion};
+use crate::signature::{self, Field};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs the reproducer in `source` and prints what happened to the class
+/// `name` as JSON.
+const HARNESS: &str = r#"
+import inspect, json
+namespace = {"__name__": "reproducer"}
+try:
+    exec(compile(source, "<reproducer>", "exec"), namespace)
+except Exception as error:
+    result = {"raised": type(error).__name__, "message": str(error)}
+else:
+    subject = namespace[name]
+    try:
+        parameters = [
+            [p.name, p.default is not p.empty]
+            for p in inspect.signature(subject).parameters.values()
+        ]
+    except (TypeError, ValueError):
+        parameters = None
+    result = {"raised": None, "parameters": parameters}
+print(json.dumps(result))
+"#;
+
+/// A local interpreter.
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    pub path: PathBuf,
+    pub version: PythonVersion,
+}
+
+/// Every `python3.x` on `PATH` or installed by pyenv, plus `extra`, one per
+/// supported version. Binaries that don't run, such as pyenv shims for
+/// versions that aren't selected, are skipped.
+pub fn interpreters(extra: &[PathBuf]) -> Vec<Interpreter> {
+    let mut candidates = extra.to_vec();
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    let pyenv_root = std::env::var_os("PYENV_ROOT")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".pyenv")));
+    if let Some(Ok(versions)) = pyenv_root.map(|root| std::fs::read_dir(root.join("versions"))) {
+        let mut versions: Vec<PathBuf> = versions
+            .filter_map(|entry| entry.ok().map(|entry| entry.path().join("bin")))
+            .collect();
+        versions.sort();
+        dirs.extend(versions);
+    }
+    for dir in dirs {
+        for minor in PythonVersion::OLDEST.0..=PythonVersion::NEWEST.0 {
+            candidates.push(dir.join(format!("python3.{minor}")));
+        }
+    }
+    let mut found: Vec<Interpreter> = Vec::new();
+    for path in candidates {
+        let Some(version) = probe(&path) else {
+            continue;
+        };
+        if found
+            .iter()
+            .all(|interpreter| interpreter.version != version)
+        {
+            found.push(Interpreter { path, version });
+        }
+    }
+    found.sort_by_key(|interpreter| interpreter.version);
+    found
+}
+
+fn probe(path: &Path) -> Option<PythonVersion> {
+    if !path.is_file() {
+        return None;
+    }
+    let output = Command::new(path)
+        .args([
+            "-I",
+            "-S",
+            "-c",
+            "import sys; print('%d.%d' % sys.version_info[:2])",
+        ])
+        .env_clear()
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()?.trim().parse().ok()
+}
+
+/// A self-contained program reproducing a finding, and what the checker
+/// predicts `inspect.signature` shows for the class it's about if the class
+/// is created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reproducer {
+    pub source: String,
+    /// The name of the class the finding is about, the last one in `source`.
+    pub subject: String,
+    pub behaviour: Behaviour,
+    pub parameters: Option<Parameters>,
+}
+
+/// Constructor parameter names, and whether each has a default.
+pub type Parameters = Vec<(String, bool)>;
+
+/// The names the reproducer's imports bind.
+const PREAMBLE_NAMES: &[&str] = &[
+    "annotations",
+    "typing",
+    "namedtuple",
+    "Generic",
+    "NamedTuple",
+    "NamedTupleMeta",
+    "TypeVar",
+];
+
+/// The reproducer for `diagnostic`, or `None` if its prediction isn't one
+/// that holds in isolation. NTS005's depends on the methods and slots of the
+/// other bases, which a reproducer doesn't copy.
+pub fn reproducer(project: &Project, diagnostic: &Diagnostic) -> Option<Reproducer> {
+    let behaviour = diagnostic.behaviour?;
+    let (module_id, module) = project
+        .modules()
+        .find(|(_, module)| module.source.path() == diagnostic.path)?;
+    let class = module.innermost_class(diagnostic.location)?;
+    let id = ClassId {
+        module: module_id,
+        index: module
+            .classes
+            .iter()
+            .position(|other| std::ptr::eq(other, class))?,
+    };
+    let own_parameters = || {
+        signature::class_fields(&module.source, &class.node.body)
+            .iter()
+            .map(parameter)
+            .collect()
+    };
+    let mut writer = Writer::new(project);
+    let (subject, parameters) = match (diagnostic.rule, behaviour) {
+        (Rule::NamedTupleSubclass | Rule::CollectionsNamedTupleSubclass, _) => {
+            let base = class
+                .node
+                .bases
+                .iter()
+                .find(|base| module.source.location(base.start()) == diagnostic.location)?;
+            writer.subclass(id, base)?
+        }
+        (Rule::SuperInNamedTuple, _) => {
+            let usage = if diagnostic.message.starts_with("`__class__`") {
+                "__class__"
+            } else {
+                "super()"
+            };
+            let method = format!("    def method(self):\n        return {usage}\n");
+            (writer.class(id, Some(&method)), None)
+        }
+        (
+            Rule::NamedTupleMetaclass,
+            Behaviour::NamedTupleMetaWithoutBases | Behaviour::NamedTupleMetaWithOtherBases,
+        ) => (writer.class(id, Some("")), None),
+        (Rule::NamedTupleMetaclass, Behaviour::PrivateNamedTupleBase | Behaviour::Always(_)) => {
+            (writer.class(id, Some("")), Some(own_parameters()))
+        }
+        _ => return None,
+    };
+    Some(Reproducer {
+        source: writer.finish(),
+        subject,
+        behaviour,
+        parameters,
+    })
+}
+
+fn parameter(field: &Field) -> (String, bool) {
+    (field.name.clone(), field.default.is_some())
+}
+
+/// Writes class statements from the project into a reproducer, together
+/// with the classes, named tuple types and type variables they need.
+struct Writer<'a> {
+    project: &'a Project,
+    source: String,
+    /// The names given to the classes written so far.
+    classes: HashMap<ClassId, String>,
+    /// Every name bound so far.
+    names: HashSet<String>,
+    /// Classes of which only one base is written, leading to a named tuple.
+    only_base: HashMap<ClassId, &'a Expr>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(project: &'a Project) -> Self {
+        Self {
+            project,
+            source: String::new(),
+            classes: HashMap::new(),
+            names: PREAMBLE_NAMES
+                .iter()
+                .map(|name| (*name).to_owned())
+                .collect(),
+            only_base: HashMap::new(),
+        }
+    }
+
+    fn finish(self) -> String {
+        // Annotations stay unevaluated strings, so they can name anything.
+        format!(
+            "from __future__ import annotations\nimport typing\nfrom collections import namedtuple\nfrom typing import Generic, NamedTuple, NamedTupleMeta, TypeVar\n{}",
+            self.source
+        )
+    }
+
+    /// `name`, or `name` with underscores appended if it's already bound.
+    fn fresh(&mut self, name: &str) -> String {
+        let mut name = name.to_owned();
+        while self.names.contains(&name) {
+            name.push('_');
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    /// Writes the subclass `id` keeping only its base `base`, and the classes
+    /// between it and the named tuple it inherits from, returning its name
+    /// and the named tuple's parameters.
+    fn subclass(&mut self, id: ClassId, base: &'a Expr) -> Option<(String, Option<Parameters>)> {
+        let project = self.project;
+        let class = project.class(id);
+        self.only_base.insert(id, base);
+        let value = match project.resolve(id.module, class.scope, base) {
+            Value::Class(base_id) if project.class_kind(base_id) == ClassKind::Regular => {
+                let ancestor = rules::typed_ancestor(project, base_id, &mut HashSet::new())?;
+                let mut through = vec![base_id];
+                through.extend(ancestor.through);
+                for (i, between) in through.iter().enumerate() {
+                    let between_class = project.class(*between);
+                    let link = match through.get(i + 1) {
+                        Some(next) => between_class.node.bases.iter().find(|base| {
+                            project.resolve(between.module, between_class.scope, base)
+                                == Value::Class(*next)
+                        })?,
+                        None => ancestor.base,
+                    };
+                    self.only_base.insert(*between, link);
+                }
+                ancestor.value
+            }
+            value => value,
+        };
+        let parameters = match &value {
+            Value::Class(base_id) => {
+                let base_class = project.class(*base_id);
+                let fields = signature::class_fields(
+                    &project.module(base_id.module).source,
+                    &base_class.node.body,
+                );
+                Some(fields.iter().map(parameter).collect())
+            }
+            Value::Functional(functional) => functional
+                .fields
+                .as_ref()
+                .map(|fields| fields.iter().map(parameter).collect()),
+            _ => None,
+        };
+        Some((self.class(id, Some("")), parameters))
+    }
+
+    /// Writes the class statement `id` with its fields, type parameters,
+    /// bases and metaclass as written, after whatever they refer to, and
+    /// returns the name it's bound to. The subject of the reproducer gets
+    /// `extra` added to its body; named tuples written as its bases keep
+    /// only their `NamedTuple` and `Generic` bases.
+    fn class(&mut self, id: ClassId, extra: Option<&str>) -> String {
+        if let Some(name) = self.classes.get(&id) {
+            return name.clone();
+        }
+        let project = self.project;
+        let class = project.class(id);
+        let source = &project.module(id.module).source;
+        let name = self.fresh(&class.name);
+        self.classes.insert(id, name.clone());
+        let only_named_tuple_bases =
+            extra.is_none() && project.class_kind(id) == ClassKind::TypedNamedTuple;
+        let bases: Vec<&Expr> = match self.only_base.get(&id) {
+            Some(base) => vec![*base],
+            None => class.node.bases.iter().collect(),
+        };
+        let mut arguments = Vec::new();
+        for base in bases {
+            let value = project.resolve(id.module, class.scope, base);
+            if only_named_tuple_bases
+                && !matches!(
+                    value,
+                    Value::Factory(Factory::Typing) | Value::PrivateNamedTuple | Value::Generic
+                )
+            {
+                continue;
+            }
+            arguments.extend(self.base(id, base, value));
+        }
+        let metaclass = class.node.keywords.iter().find(|keyword| {
+            keyword
+                .arg
+                .as_ref()
+                .is_some_and(|arg| arg.as_str() == "metaclass")
+        });
+        if let Some(metaclass) = metaclass {
+            match project.resolve(id.module, class.scope, &metaclass.value) {
+                Value::NamedTupleMeta => arguments.push("metaclass=NamedTupleMeta".to_owned()),
+                Value::Class(meta) => {
+                    let meta = self.fresh(&project.class(meta).name);
+                    writeln!(self.source, "class {meta}(NamedTupleMeta):\n    pass").unwrap();
+                    arguments.push(format!("metaclass={meta}"));
+                }
+                _ => {}
+            }
+        }
+        let type_params = match (
+            class.node.type_params.first(),
+            class.node.type_params.last(),
+        ) {
+            (Some(first), Some(last)) => format!(
+                "[{}]",
+                source.slice(TextRange::new(first.start(), last.end()))
+            ),
+            _ => String::new(),
+        };
+        let arguments = if arguments.is_empty() {
+            String::new()
+        } else {
+            format!("({})", arguments.join(", "))
+        };
+        writeln!(self.source, "class {name}{type_params}{arguments}:").unwrap();
+        let fields = signature::class_fields(source, &class.node.body);
+        write_fields(&mut self.source, &fields);
+        let extra = extra.unwrap_or_default();
+        self.source.push_str(extra);
+        if fields.is_empty() && extra.is_empty() {
+            self.source.push_str("    pass\n");
+        }
+        name
+    }
+
+    /// The base `expr` of the class `id`, which evaluates to `value`, as it
+    /// appears in the reproducer, or `None` if it's left out.
+    fn base(&mut self, id: ClassId, expr: &Expr, value: Value) -> Option<String> {
+        let name = match value {
+            Value::Factory(Factory::Typing) => return Some("NamedTuple".to_owned()),
+            Value::PrivateNamedTuple => return Some("typing._NamedTuple".to_owned()),
+            Value::Generic => {
+                return Some(match expr {
+                    Expr::Subscript(subscript) => {
+                        format!("Generic[{}]", self.type_vars(&subscript.slice))
+                    }
+                    _ => "Generic".to_owned(),
+                })
+            }
+            Value::Builtin(name) => return Some(name),
+            Value::Class(base) => self.class(base, None),
+            Value::Functional(functional) => self.functional(&functional),
+            // Only the fact that there is another base matters to NTS004.
+            Value::Unknown => {
+                let name = match expr {
+                    Expr::Name(name) => self.fresh(&name.id),
+                    Expr::Attribute(attribute) => self.fresh(&attribute.attr),
+                    _ => self.fresh("Base"),
+                };
+                writeln!(self.source, "class {name}:\n    pass").unwrap();
+                name
+            }
+            _ => return None,
+        };
+        // Subscripting the base is what fails on 3.8, so keep it, but with
+        // type arguments that can't fail to evaluate.
+        Some(match expr {
+            Expr::Subscript(subscript) => {
+                let class = self.project.class(id);
+                let arguments: Vec<String> = elements(&subscript.slice)
+                    .iter()
+                    .map(
+                        |argument| match self.project.resolve(id.module, class.scope, argument) {
+                            Value::Builtin(builtin) => builtin,
+                            _ => "object".to_owned(),
+                        },
+                    )
+                    .collect();
+                format!("{name}[{}]", arguments.join(", "))
+            }
+            _ => name,
+        })
+    }
+
+    /// The type arguments of `Generic[...]`, defining a type variable for
+    /// each name and standing in a new one for anything else.
+    fn type_vars(&mut self, slice: &Expr) -> String {
+        let mut names = Vec::new();
+        for element in elements(slice) {
+            let name = match element {
+                Expr::Name(name) if self.names.contains(name.id.as_str()) => {
+                    names.push(name.id.to_string());
+                    continue;
+                }
+                Expr::Name(name) => self.fresh(&name.id),
+                _ => self.fresh("T"),
+            };
+            writeln!(self.source, "{name} = TypeVar({name:?})").unwrap();
+            names.push(name);
+        }
+        names.join(", ")
+    }
+
+    /// Writes the call creating `functional`, returning the name it's
+    /// assigned to.
+    fn functional(&mut self, functional: &FunctionalNamedTuple) -> String {
+        let is_identifier = functional
+            .typename
+            .chars()
+            .next()
+            .is_some_and(|first| first == '_' || first.is_alphabetic())
+            && functional
+                .typename
+                .chars()
+                .all(|c| c == '_' || c.is_alphanumeric());
+        let typename = if is_identifier {
+            functional.typename.as_str()
+        } else {
+            "Base"
+        };
+        let name = self.fresh(typename);
+        let fields = functional.fields.as_deref().unwrap_or_default();
+        let quote = |text: &str| serde_json::to_string(text).unwrap();
+        match functional.factory {
+            Factory::Typing => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|field| {
+                        let annotation = field.annotation.as_deref().unwrap_or("object");
+                        format!("({}, {})", quote(&field.name), quote(annotation))
+                    })
+                    .collect();
+                writeln!(
+                    self.source,
+                    "{name} = NamedTuple({}, [{}])",
+                    quote(typename),
+                    fields.join(", ")
+                )
+                .unwrap();
+            }
+            Factory::Collections => {
+                let names: Vec<String> = fields.iter().map(|field| quote(&field.name)).collect();
+                let defaults: Vec<&str> = fields
+                    .iter()
+                    .filter_map(|field| field.default.as_deref().map(literal_or_none))
+                    .collect();
+                writeln!(
+                    self.source,
+                    "{name} = namedtuple({}, [{}], defaults=[{}])",
+                    quote(typename),
+                    names.join(", "),
+                    defaults.join(", ")
+                )
+                .unwrap();
+            }
+        }
+        name
+    }
+}
+
+/// The elements of a subscript's slice: a tuple's, or the slice itself.
+fn elements(slice: &Expr) -> Vec<&Expr> {
+    match slice {
+        Expr::Tuple(tuple) => tuple.elts.iter().collect(),
+        slice => vec![slice],
+    }
+}
+
+/// Writes `fields` as a class body, with their annotations as written.
+/// Defaults are kept if they're literals and replaced by `None` otherwise,
+/// so they can't fail to evaluate.
+fn write_fields(source: &mut String, fields: &[Field]) {
+    for field in fields {
+        let annotation = field.annotation.as_deref().unwrap_or("object");
+        match field.default.as_deref() {
+            Some(default) => writeln!(
+                source,
+                "    {}: {annotation} = {}",
+                field.name,
+                literal_or_none(default)
+            ),
+            None => writeln!(source, "    {}: {annotation}", field.name),
+        }
+        .unwrap();
+    }
+}
+
+/// `text` if it's a literal expression, or `None`.
+fn literal_or_none(text: &str) -> &str {
+    match ast::Expr::parse(text, "<default>") {
+        Ok(expr) if is_literal(&expr) => text,
+        _ => "None",
+    }
+}
+
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Constant(_) => true,
+        Expr::UnaryOp(ast::ExprUnaryOp { operand, .. }) => is_literal(operand),
+        Expr::Tuple(ast::ExprTuple { elts, .. })
+        | Expr::List(ast::ExprList { elts, .. })
+        | Expr::Set(ast::ExprSet { elts, .. }) => elts.iter().all(is_literal),
+        Expr::Dict(ast::ExprDict { keys, values, .. }) => {
+            keys.iter().flatten().all(is_literal) && values.iter().all(is_literal)
+        }
+        _ => false,
+    }
+}
+
+/// What running a reproducer showed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Observation {
+    /// The exception the reproducer raised, if any.
+    pub raised: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The subject's parameters and whether each has a default.
+    #[serde(default)]
+    pub parameters: Option<Parameters>,
+}
+
+impl Observation {
+    fn describe(&self) -> String {
+        match (&self.raised, &self.message, &self.parameters) {
+            (Some(raised), Some(message), _) => format!("raises `{raised}: {message}`"),
+            (Some(raised), None, _) => format!("raises `{raised}`"),
+            (None, _, Some(parameters)) => {
+                format!("creates the class with {}", signature(parameters))
+            }
+            (None, _, None) => "creates the class".to_owned(),
+        }
+    }
+}
+
+fn signature(parameters: &[(String, bool)]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|(name, has_default)| {
+            if *has_default {
+                format!("{name}=...")
+            } else {
+                name.clone()
+            }
+        })
+        .collect();
+    format!("({})", parameters.join(", "))
+}
+
+/// Runs `reproducer` in `interpreter`.
+pub fn run(interpreter: &Interpreter, reproducer: &Reproducer) -> io::Result<Observation> {
+    let dir = std::env::temp_dir().join(format!(
+        "check-namedtuple-subclasses-verify-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir)?;
+    let source = serde_json::to_string(&reproducer.source).map_err(io::Error::from)?;
+    let name = serde_json::to_string(&reproducer.subject).map_err(io::Error::from)?;
+    let program = format!("source = {source}\nname = {name}\n{HARNESS}");
+    let mut child = Command::new(&interpreter.path)
+        .args(["-I", "-S", "-B", "-c", &program])
+        .env_clear()
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > TIMEOUT {
+            child.kill()?;
+            child.wait()?;
+            let _ = std::fs::remove_dir(&dir);
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{}: reproducer timed out", interpreter.path.display()),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    let _ = std::fs::remove_dir(&dir);
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    if let Some(mut out) = child.stdout.take() {
+        out.read_to_string(&mut stdout)?;
+    }
+    if let Some(mut err) = child.stderr.take() {
+        err.read_to_string(&mut stderr)?;
+    }
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{}: reproducer harness failed: {}",
+            interpreter.path.display(),
+            stderr.trim()
+        )));
+    }
+    serde_json::from_str(stdout.trim()).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{}: unexpected harness output: {err}",
+                interpreter.path.display()
+            ),
+        )
+    })
+}
+
+/// Compares what the checker predicts for `version` with what was observed,
+/// returning a description of the difference if there is one.
+pub fn compare(
+    reproducer: &Reproducer,
+    version: PythonVersion,
+    observation: &Observation,
+) -> Option<String> {
+    let predicted = reproducer.behaviour.outcome(version);
+    let matches = match (predicted, &observation.raised) {
+        (Outcome::Raises(_), Some(raised)) => predicted
+            .exception()
+            .map_or(true, |exception| exception == raised),
+        (Outcome::Raises(_), None) => false,
+        (Outcome::Misbehaves(_) | Outcome::Works, Some(_)) => false,
+        (Outcome::Misbehaves(_) | Outcome::Works, None) => {
+            match (&reproducer.parameters, &observation.parameters) {
+                (Some(expected), Some(observed)) => expected == observed,
+                _ => true,
+            }
+        }
+    };
+    if matches {
+        return None;
+    }
+    let mut predicted = predicted.to_string();
+    if let (Some(parameters), Outcome::Misbehaves(_) | Outcome::Works) = (
+        &reproducer.parameters,
+        reproducer.behaviour.outcome(version),
+    ) {
+        write!(predicted, " with {}", signature(parameters)).unwrap();
+    }
+    Some(format!(
+        "predicted {predicted}, but it {}",
+        observation.describe()
+    ))
+}
+
+/// Runs each distinct reproducer once per interpreter.
+#[derive(Debug, Default)]
+pub struct Runner {
+    cache: HashMap<(String, PythonVersion), Observation>,
+}
+
+impl Runner {
+    pub fn observe(
+        &mut self,
+        interpreter: &Interpreter,
+        reproducer: &Reproducer,
+    ) -> io::Result<&Observation> {
+        let key = (reproducer.source.clone(), interpreter.version);
+        if !self.cache.contains_key(&key) {
+            let observation = run(interpreter, reproducer)?;
+            self.cache.insert(key.clone(), observation);
+        }
+        Ok(&self.cache[&key])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::tests::project;
+    use crate::runtime::GenericBase;
+
+    /// The reproducers of the findings on the class `subject`.
+    fn reproducers(text: &str, subject: &str) -> Vec<Reproducer> {
+        let project = project(&[("m", false, text)]);
+        rules::check(&project, Rule::ALL)
+            .iter()
+            .filter_map(|diagnostic| reproducer(&project, diagnostic))
+            .filter(|reproducer| reproducer.subject == subject)
+            .collect()
+    }
+
+    /// `source` without the imports every reproducer starts with.
+    fn body(source: &str) -> &str {
+        source.split_once("TypeVar\n").unwrap().1
+    }
+
+    #[test]
+    fn subclasses_keep_fields_defaults_and_generic_form() {
+        let text = "from typing import Generic, NamedTuple, TypeVar\nfrom other import make, Unknown\nT = TypeVar('T')\nclass Pair(NamedTuple, Generic[T]):\n    first: T\n    second: 'T' = (1, -2)\n    third: int = make()\nclass IntPair(Pair[int]):\n    extra: list[Unknown] = []\nclass Box[U: int](NamedTuple):\n    item: U\nclass UnknownBox(Box[Unknown]): pass\n";
+        let [pair] = reproducers(text, "IntPair").try_into().unwrap();
+        assert_eq!(
+            body(&pair.source),
+            "T = TypeVar(\"T\")\nclass Pair(NamedTuple, Generic[T]):\n    first: T\n    second: 'T' = (1, -2)\n    third: int = None\nclass IntPair(Pair[int]):\n    extra: list[Unknown] = []\n"
+        );
+        assert_eq!(pair.subject, "IntPair");
+        assert_eq!(
+            pair.parameters,
+            Some(vec![
+                ("first".to_owned(), false),
+                ("second".to_owned(), true),
+                ("third".to_owned(), true),
+            ])
+        );
+        let [boxed] = reproducers(text, "UnknownBox").try_into().unwrap();
+        assert_eq!(
+            body(&boxed.source),
+            "class Box[U: int](NamedTuple):\n    item: U\nclass UnknownBox(Box[object]):\n    pass\n"
+        );
+    }
+
+    #[test]
+    fn subclasses_keep_only_the_bases_leading_to_the_named_tuple() {
+        let text = "import collections\nfrom typing import NamedTuple\nclass Mixin: pass\nPoint = NamedTuple('Point', [('x', int), ('y', 'float')])\nclass Mid(Mixin, Point):\n    def helper(self): ...\nclass Leaf(Mid):\n    z: int = 0\nclass Pair(collections.namedtuple('Pair', 'a b', defaults=[f()])): pass\nclass Inline(NamedTuple('<unnamed>', x=int), Mixin): pass\n";
+        let [leaf] = reproducers(text, "Leaf").try_into().unwrap();
+        assert_eq!(
+            body(&leaf.source),
+            "Point = NamedTuple(\"Point\", [(\"x\", \"int\"), (\"y\", \"'float'\")])\nclass Mid(Point):\n    pass\nclass Leaf(Mid):\n    z: int = 0\n"
+        );
+        assert_eq!(
+            leaf.parameters,
+            Some(vec![("x".to_owned(), false), ("y".to_owned(), false)])
+        );
+        let [pair] = reproducers(text, "Pair").try_into().unwrap();
+        assert_eq!(
+            body(&pair.source),
+            "Pair_ = namedtuple(\"Pair\", [\"a\", \"b\"], defaults=[None])\nclass Pair(Pair_):\n    pass\n"
+        );
+        assert_eq!(pair.subject, "Pair");
+        let [inline] = reproducers(text, "Inline").try_into().unwrap();
+        assert_eq!(
+            body(&inline.source),
+            "Base = NamedTuple(\"Base\", [(\"x\", \"int\")])\nclass Inline(Base):\n    pass\n"
+        );
+    }
+
+    #[test]
+    fn metaclass_and_class_cell_reproducers() {
+        let text = "import typing\nfrom typing import NamedTuple, NamedTupleMeta\nfrom other import Mixin\nclass Meta(NamedTupleMeta): pass\nclass Bad(Mixin, metaclass=Meta):\n    y: int\nclass Private(typing._NamedTuple):\n    z: int = 1\nclass Cls(NamedTuple):\n    x: int\n    def method(self):\n        return __class__\n";
+        let [bad] = reproducers(text, "Bad").try_into().unwrap();
+        assert_eq!(
+            body(&bad.source),
+            "class Mixin:\n    pass\nclass Meta(NamedTupleMeta):\n    pass\nclass Bad(Mixin, metaclass=Meta):\n    y: int\n"
+        );
+        assert_eq!(bad.parameters, None);
+        let [private] = reproducers(text, "Private").try_into().unwrap();
+        assert_eq!(
+            body(&private.source),
+            "class Private(typing._NamedTuple):\n    z: int = 1\n"
+        );
+        assert_eq!(private.parameters, Some(vec![("z".to_owned(), true)]));
+        let [cls] = reproducers(text, "Cls").try_into().unwrap();
+        assert_eq!(
+            body(&cls.source),
+            "class Cls(NamedTuple):\n    x: int\n    def method(self):\n        return __class__\n"
+        );
+    }
+
+    fn observation(raised: Option<&str>, parameters: &[(&str, bool)]) -> Observation {
+        Observation {
+            raised: raised.map(str::to_owned),
+            message: None,
+            parameters: raised.is_none().then(|| {
+                parameters
+                    .iter()
+                    .map(|(name, has_default)| ((*name).to_owned(), *has_default))
+                    .collect()
+            }),
+        }
+    }
+
+    #[test]
+    fn compare_with_observations() {
+        let reproducer = Reproducer {
+            source: String::new(),
+            subject: "IntPair".to_owned(),
+            behaviour: Behaviour::Subclass {
+                outcome: Outcome::Misbehaves("the declared fields are ignored"),
+                generic: Some(GenericBase {
+                    type_params: false,
+                    subscripted: true,
+                }),
+            },
+            parameters: Some(vec![("first".to_owned(), false)]),
+        };
+        let created = observation(None, &[("first", false)]);
+        let raised = observation(Some("TypeError"), &[]);
+        assert_eq!(compare(&reproducer, PythonVersion(8), &raised), None);
+        assert_eq!(compare(&reproducer, PythonVersion(11), &created), None);
+        assert_eq!(
+            compare(&reproducer, PythonVersion(8), &created).unwrap(),
+            "predicted raises `TypeError: 'type' object is not subscriptable` at the base, but it creates the class with (first)"
+        );
+        assert_eq!(
+            compare(&reproducer, PythonVersion(11), &raised).unwrap(),
+            "predicted misbehaves: the declared fields are ignored with (first), but it raises `TypeError`"
+        );
+        assert_eq!(
+            compare(
+                &reproducer,
+                PythonVersion(12),
+                &observation(None, &[("first", false), ("second", true)])
+            )
+            .unwrap(),
+            "predicted misbehaves: the declared fields are ignored with (first), but it creates the class with (first, second=...)"
+        );
+        let without_parameters = Reproducer {
+            parameters: None,
+            ..reproducer
+        };
+        assert_eq!(
+            compare(&without_parameters, PythonVersion(12), &created),
+            None
+        );
+    }
+}
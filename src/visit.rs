@@ -0,0 +1,355 @@
+//! Borrowing traversal of the Python AST.
+//!
+//! Implementors override the `visit_*` methods they care about and call the
+//! matching `walk_*` function to keep descending.
+
+use rustpython_parser::ast::{self, Expr, Stmt};
+
+pub trait Visitor<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_body(&mut self, body: &'a [Stmt]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+}
+
+pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
+    match stmt {
+        Stmt::FunctionDef(ast::StmtFunctionDef {
+            args,
+            body,
+            decorator_list,
+            returns,
+            ..
+        })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+            args,
+            body,
+            decorator_list,
+            returns,
+            ..
+        }) => {
+            for decorator in decorator_list {
+                visitor.visit_expr(decorator);
+            }
+            walk_arguments(visitor, args);
+            if let Some(returns) = returns {
+                visitor.visit_expr(returns);
+            }
+            visitor.visit_body(body);
+        }
+        Stmt::ClassDef(ast::StmtClassDef {
+            bases,
+            keywords,
+            body,
+            decorator_list,
+            ..
+        }) => {
+            for decorator in decorator_list {
+                visitor.visit_expr(decorator);
+            }
+            for base in bases {
+                visitor.visit_expr(base);
+            }
+            for keyword in keywords {
+                visitor.visit_expr(&keyword.value);
+            }
+            visitor.visit_body(body);
+        }
+        Stmt::Return(ast::StmtReturn { value, .. }) => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        Stmt::Delete(ast::StmtDelete { targets, .. }) => {
+            for target in targets {
+                visitor.visit_expr(target);
+            }
+        }
+        Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+            visitor.visit_expr(value);
+            for target in targets {
+                visitor.visit_expr(target);
+            }
+        }
+        Stmt::TypeAlias(ast::StmtTypeAlias { name, value, .. }) => {
+            visitor.visit_expr(value);
+            visitor.visit_expr(name);
+        }
+        Stmt::AugAssign(ast::StmtAugAssign { target, value, .. }) => {
+            visitor.visit_expr(value);
+            visitor.visit_expr(target);
+        }
+        Stmt::AnnAssign(ast::StmtAnnAssign {
+            target,
+            annotation,
+            value,
+            ..
+        }) => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+            visitor.visit_expr(annotation);
+            visitor.visit_expr(target);
+        }
+        Stmt::For(ast::StmtFor {
+            target,
+            iter,
+            body,
+            orelse,
+            ..
+        })
+        | Stmt::AsyncFor(ast::StmtAsyncFor {
+            target,
+            iter,
+            body,
+            orelse,
+            ..
+        }) => {
+            visitor.visit_expr(iter);
+            visitor.visit_expr(target);
+            visitor.visit_body(body);
+            visitor.visit_body(orelse);
+        }
+        Stmt::While(ast::StmtWhile {
+            test, body, orelse, ..
+        })
+        | Stmt::If(ast::StmtIf {
+            test, body, orelse, ..
+        }) => {
+            visitor.visit_expr(test);
+            visitor.visit_body(body);
+            visitor.visit_body(orelse);
+        }
+        Stmt::With(ast::StmtWith { items, body, .. })
+        | Stmt::AsyncWith(ast::StmtAsyncWith { items, body, .. }) => {
+            for item in items {
+                visitor.visit_expr(&item.context_expr);
+                if let Some(vars) = &item.optional_vars {
+                    visitor.visit_expr(vars);
+                }
+            }
+            visitor.visit_body(body);
+        }
+        Stmt::Match(ast::StmtMatch { subject, cases, .. }) => {
+            visitor.visit_expr(subject);
+            for case in cases {
+                if let Some(guard) = &case.guard {
+                    visitor.visit_expr(guard);
+                }
+                visitor.visit_body(&case.body);
+            }
+        }
+        Stmt::Raise(ast::StmtRaise { exc, cause, .. }) => {
+            if let Some(exc) = exc {
+                visitor.visit_expr(exc);
+            }
+            if let Some(cause) = cause {
+                visitor.visit_expr(cause);
+            }
+        }
+        Stmt::Try(ast::StmtTry {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        })
+        | Stmt::TryStar(ast::StmtTryStar {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        }) => {
+            visitor.visit_body(body);
+            for ast::ExceptHandler::ExceptHandler(handler) in handlers {
+                if let Some(type_) = &handler.type_ {
+                    visitor.visit_expr(type_);
+                }
+                visitor.visit_body(&handler.body);
+            }
+            visitor.visit_body(orelse);
+            visitor.visit_body(finalbody);
+        }
+        Stmt::Assert(ast::StmtAssert { test, msg, .. }) => {
+            visitor.visit_expr(test);
+            if let Some(msg) = msg {
+                visitor.visit_expr(msg);
+            }
+        }
+        Stmt::Expr(ast::StmtExpr { value, .. }) => visitor.visit_expr(value),
+        Stmt::Import(_)
+        | Stmt::ImportFrom(_)
+        | Stmt::Global(_)
+        | Stmt::Nonlocal(_)
+        | Stmt::Pass(_)
+        | Stmt::Break(_)
+        | Stmt::Continue(_) => {}
+    }
+}
+
+pub fn walk_arguments<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, args: &'a ast::Arguments) {
+    let with_defaults = args
+        .posonlyargs
+        .iter()
+        .chain(&args.args)
+        .chain(&args.kwonlyargs);
+    for arg in with_defaults {
+        if let Some(default) = &arg.default {
+            visitor.visit_expr(default);
+        }
+        if let Some(annotation) = &arg.def.annotation {
+            visitor.visit_expr(annotation);
+        }
+    }
+    for arg in args.vararg.iter().chain(&args.kwarg) {
+        if let Some(annotation) = &arg.annotation {
+            visitor.visit_expr(annotation);
+        }
+    }
+}
+
+pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
+    match expr {
+        Expr::BoolOp(ast::ExprBoolOp { values, .. }) => {
+            for value in values {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::NamedExpr(ast::ExprNamedExpr { target, value, .. }) => {
+            visitor.visit_expr(value);
+            visitor.visit_expr(target);
+        }
+        Expr::BinOp(ast::ExprBinOp { left, right, .. }) => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::UnaryOp(ast::ExprUnaryOp { operand, .. }) => visitor.visit_expr(operand),
+        Expr::Lambda(ast::ExprLambda { args, body, .. }) => {
+            walk_arguments(visitor, args);
+            visitor.visit_expr(body);
+        }
+        Expr::IfExp(ast::ExprIfExp {
+            test, body, orelse, ..
+        }) => {
+            visitor.visit_expr(test);
+            visitor.visit_expr(body);
+            visitor.visit_expr(orelse);
+        }
+        Expr::Dict(ast::ExprDict { keys, values, .. }) => {
+            for key in keys.iter().flatten() {
+                visitor.visit_expr(key);
+            }
+            for value in values {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Set(ast::ExprSet { elts, .. })
+        | Expr::List(ast::ExprList { elts, .. })
+        | Expr::Tuple(ast::ExprTuple { elts, .. }) => {
+            for elt in elts {
+                visitor.visit_expr(elt);
+            }
+        }
+        Expr::ListComp(ast::ExprListComp {
+            elt, generators, ..
+        })
+        | Expr::SetComp(ast::ExprSetComp {
+            elt, generators, ..
+        })
+        | Expr::GeneratorExp(ast::ExprGeneratorExp {
+            elt, generators, ..
+        }) => {
+            walk_comprehensions(visitor, generators);
+            visitor.visit_expr(elt);
+        }
+        Expr::DictComp(ast::ExprDictComp {
+            key,
+            value,
+            generators,
+            ..
+        }) => {
+            walk_comprehensions(visitor, generators);
+            visitor.visit_expr(key);
+            visitor.visit_expr(value);
+        }
+        Expr::Await(ast::ExprAwait { value, .. })
+        | Expr::YieldFrom(ast::ExprYieldFrom { value, .. })
+        | Expr::Attribute(ast::ExprAttribute { value, .. })
+        | Expr::Starred(ast::ExprStarred { value, .. }) => visitor.visit_expr(value),
+        Expr::Yield(ast::ExprYield { value, .. }) => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Compare(ast::ExprCompare {
+            left, comparators, ..
+        }) => {
+            visitor.visit_expr(left);
+            for comparator in comparators {
+                visitor.visit_expr(comparator);
+            }
+        }
+        Expr::Call(ast::ExprCall {
+            func,
+            args,
+            keywords,
+            ..
+        }) => {
+            visitor.visit_expr(func);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for keyword in keywords {
+                visitor.visit_expr(&keyword.value);
+            }
+        }
+        Expr::FormattedValue(ast::ExprFormattedValue {
+            value, format_spec, ..
+        }) => {
+            visitor.visit_expr(value);
+            if let Some(format_spec) = format_spec {
+                visitor.visit_expr(format_spec);
+            }
+        }
+        Expr::JoinedStr(ast::ExprJoinedStr { values, .. }) => {
+            for value in values {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Subscript(ast::ExprSubscript { value, slice, .. }) => {
+            visitor.visit_expr(value);
+            visitor.visit_expr(slice);
+        }
+        Expr::Slice(ast::ExprSlice {
+            lower, upper, step, ..
+        }) => {
+            for part in [lower, upper, step].into_iter().flatten() {
+                visitor.visit_expr(part);
+            }
+        }
+        Expr::Constant(_) | Expr::Name(_) => {}
+    }
+}
+
+fn walk_comprehensions<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    generators: &'a [ast::Comprehension],
+) {
+    for generator in generators {
+        visitor.visit_expr(&generator.iter);
+        visitor.visit_expr(&generator.target);
+        for condition in &generator.ifs {
+            visitor.visit_expr(condition);
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! Runs the command line tool on the fixture trees in `tests/fixtures`.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const BIN: &str = env!("CARGO_BIN_EXE_check-namedtuple-subclasses");
+
+/// A fresh copy of `tests/fixtures/<name>` for `test` to run in and change.
+fn fixture(name: &str, test: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test);
+    let _ = fs::remove_dir_all(&dir);
+    copy_dir(
+        &Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+        &dir,
+    );
+    dir
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(BIN)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// The rule and class of every finding, like `NTS001 Point3D`.
+fn findings(output: &Output) -> Vec<String> {
+    stdout(output)
+        .lines()
+        .filter(|line| !line.starts_with(' '))
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(": NTS")?;
+            let (code, rest) = rest.split_once(' ')?;
+            let class = rest.split('`').nth(1)?;
+            Some(format!("NTS{code} {class}"))
+        })
+        .collect()
+}
+
+/// A Python interpreter to run rewritten code with, if there is one.
+fn python() -> Option<&'static str> {
+    ["python3", "python"].into_iter().find(|python| {
+        Command::new(python)
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    })
+}
+
+fn assert_runs(dir: &Path, script: &str) {
+    let Some(python) = python() else {
+        eprintln!("no Python interpreter, not running the rewritten code");
+        return;
+    };
+    let output = Command::new(python)
+        .args(["-c", script])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn bases_resolve_through_package_reexports() {
+    let dir = fixture("reexport", "reexport");
+    let output = run(&dir, &["check", "."]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        findings(&output),
+        [
+            "NTS001 Vector3D",
+            "NTS001 Labelled",
+            "NTS001 Qualified",
+            "NTS001 Starred"
+        ]
+    );
+    assert_eq!(
+        stdout(&output)
+            .matches("inherits from typed NamedTuple `pkg.models.Point`")
+            .count(),
+        4
+    );
+}
+
+#[test]
+fn multiple_inheritance_notes() {
+    let dir = fixture("multiple_inheritance", "multiple_inheritance");
+    let output = run(&dir, &["check", "--select", "NTS005", "."]);
+    assert_eq!(
+        findings(&output),
+        [
+            "NTS005 LoggedPoint",
+            "NTS005 ReplacedPoint",
+            "NTS005 TaggedPoint",
+            "NTS005 SlottedPoint"
+        ]
+    );
+    let text = stdout(&output);
+    for note in [
+        "  `Logged.__init__` runs after `Point.__new__` with the same arguments\n",
+        "  MRO: LoggedPoint, Point, tuple, Logged, object\n",
+        "  `Replacing.__new__` comes before `Point` in the MRO and replaces the named tuple constructor without calling `super().__new__`, so the fields are never set\n",
+        "  annotations `tag` of `Tagged` never become fields of the named tuple\n",
+        "  `Slotted` declares non-empty `__slots__`, which can't be combined with `tuple`'s\n",
+    ] {
+        assert!(text.contains(note), "missing {note:?} in:\n{text}");
+    }
+}
+
+#[test]
+fn mine_summarises_projects_reproducibly() {
+    let dir = fixture("corpus", "mine");
+    let output = run(&dir, &["mine", "."]);
+    assert_eq!(output.status.code(), Some(0));
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["corpus"]["projects"], 3);
+    let projects: Vec<_> = summary["projects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row["name"].as_str().unwrap(),
+                row["files"].as_u64().unwrap(),
+                row["parse_errors"].as_u64().unwrap(),
+                row["typed_namedtuples"].as_u64().unwrap(),
+                row["subclasses"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        projects,
+        [
+            (".", 1, 0, 1, 0),
+            ("alpha", 2, 0, 1, 1),
+            ("beta", 2, 1, 1, 1)
+        ]
+    );
+    assert_eq!(summary["totals"]["files"], 5);
+    assert_eq!(summary["totals"]["subclasses"], 2);
+    assert_eq!(summary["totals"]["intents"]["ignored-fields"], 1);
+    assert_eq!(summary["totals"]["intents"]["dunder-overrides"], 1);
+    assert_eq!(
+        summary["findings"][0]["locations"][0],
+        "alpha/models.py:9:15"
+    );
+
+    // The same corpus unpacked elsewhere summarises identically.
+    let elsewhere = fixture("corpus", "mine_elsewhere");
+    let again = run(&elsewhere.join(".."), &["mine", "mine_elsewhere"]);
+    assert_eq!(stdout(&again), stdout(&output));
+}
+
+#[test]
+fn mine_counts_copies_once() {
+    let dir = fixture("copies", "mine_copies");
+    let output = run(&dir, &["mine", "."]);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["corpus"]["duplicate_files"], 1);
+    assert_eq!(summary["totals"]["files"], 3);
+    assert_eq!(summary["totals"]["read_errors"], 1);
+    assert_eq!(summary["projects"][1]["subclasses"], 1);
+    // `beta/sub.py` is a copy of `alpha/sub.py`, but only `Y`'s base is a
+    // named tuple there, so only `Y` is found at both locations.
+    let findings: Vec<_> = summary["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|finding| {
+            let message = finding["message"].as_str().unwrap();
+            (
+                message.split('`').nth(1).unwrap(),
+                finding["locations"].as_array().unwrap().len(),
+            )
+        })
+        .collect();
+    assert_eq!(findings, [("X", 1), ("Y", 2)]);
+}
+
+#[test]
+fn sarif_locations_are_relative_to_the_root() {
+    let dir = fixture("reexport", "sarif");
+    let output = run(
+        &dir.join("pkg"),
+        &["check", "--output-format", "sarif", ".."],
+    );
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let run = &log["runs"][0];
+    let root = run["originalUriBaseIds"]["%SRCROOT%"]["uri"]
+        .as_str()
+        .unwrap();
+    assert!(
+        root.starts_with("file:///") && root.ends_with("/sarif/"),
+        "{root}"
+    );
+    let artifact = &run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"];
+    assert_eq!(artifact["uri"], "app.py");
+    assert_eq!(artifact["uriBaseId"], "%SRCROOT%");
+}
+
+#[test]
+fn standalone_fix_round_trip() {
+    let dir = fixture("fix", "fix_standalone");
+    let output = run(&dir, &["check", "--fix", "shapes.py"]);
+    assert!(
+        stderr(&output).contains("Rewrote 1 of 1 classes."),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(run(&dir, &["check", "shapes.py"]).status.code(), Some(0));
+    assert_runs(
+        &dir,
+        "from shapes import Point3D\n\
+         p = Point3D(1, -2, 3)\n\
+         assert p._fields == ('x', 'y', 'z'), p._fields\n\
+         assert (p.z, p.norm()) == (3, 3)\n\
+         assert Point3D(1, 2).z == 0\n",
+    );
+}
+
+#[test]
+fn dataclass_fix_round_trip() {
+    let dir = fixture("fix", "fix_dataclass");
+    let output = run(
+        &dir,
+        &["check", "--fix", "--fix-strategy", "dataclass", "shapes.py"],
+    );
+    assert!(
+        stderr(&output).contains("Rewrote 2 of 2 classes."),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(run(&dir, &["check", "shapes.py"]).status.code(), Some(0));
+    assert!(!fs::read_to_string(dir.join("shapes.py"))
+        .unwrap()
+        .contains("NamedTuple"));
+    assert_runs(
+        &dir,
+        "import dataclasses\n\
+         from shapes import Point, Point3D\n\
+         p = Point3D(1, -2, 3)\n\
+         assert [f.name for f in dataclasses.fields(p)] == ['x', 'y', 'z']\n\
+         assert (p.z, p.norm()) == (3, 3)\n\
+         assert Point(1, 2) != Point3D(1, 2)\n\
+         try:\n    p.x = 0\nexcept dataclasses.FrozenInstanceError:\n    pass\n\
+         else:\n    raise AssertionError('not frozen')\n",
+    );
+}
+
+#[test]
+fn add_final_diff_and_write() {
+    let dir = fixture("add_final", "add_final");
+    let output = run(&dir, &["add-final", "."]);
+    assert_eq!(output.status.code(), Some(1));
+    let diff = stdout(&output);
+    for line in [
+        "+@te.final\n class Colour(te.NamedTuple):\n",
+        "+@final\n class Point(NamedTuple):\n",
+        "-from typing import NamedTuple, Optional\n+from typing import NamedTuple, Optional, final\n",
+    ] {
+        assert!(diff.contains(line), "missing {line:?} in:\n{diff}");
+    }
+    assert!(!diff.contains("+@final\n class Done"));
+    assert_eq!(
+        fs::read_to_string(dir.join("pairs.py")).unwrap(),
+        fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/add_final/pairs.py")
+        )
+        .unwrap()
+    );
+
+    assert_eq!(
+        run(&dir, &["add-final", "--write", "."]).status.code(),
+        Some(0)
+    );
+    assert!(fs::read_to_string(dir.join("models.py"))
+        .unwrap()
+        .contains("\n@final\nclass Point(NamedTuple):\n"));
+    let again = run(&dir, &["add-final", "."]);
+    assert_eq!(again.status.code(), Some(0));
+    assert_eq!(stdout(&again), "");
+}
+
+#[test]
+fn noqa_comments_and_overrides() {
+    let dir = fixture("suppression", "suppression");
+    let expected = ["NTS001 OtherCode", "NTS001 Reported"];
+    assert_eq!(findings(&run(&dir, &["check", "."])), expected);
+    // The settings come from the checked paths, not the working directory.
+    let parent = dir.parent().unwrap();
+    assert_eq!(findings(&run(parent, &["check", "suppression"])), expected);
+    let output = run(&dir, &["check", "tests", "skipped.py"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+}
+
+#[test]
+fn baseline_matches_moved_findings() {
+    let dir = fixture("baseline", "baseline");
+    let check = ["check", "--baseline", "baseline.json", "."];
+    let output = run(&dir, &[&check[..], &["--write-baseline"]].concat());
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(run(&dir, &check).status.code(), Some(0));
+    // Entries for rules that don't run aren't reported as fixed.
+    let output = run(&dir, &[&check[..], &["--select", "NTS005"]].concat());
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "");
+
+    let models = dir.join("models.py");
+    let text = fs::read_to_string(&models).unwrap();
+    let text = text.replace("class Old(Point):\n    pass\n", "");
+    fs::write(
+        &models,
+        format!("\n\n{text}\n\nclass New(Point):\n    pass\n"),
+    )
+    .unwrap();
+    let output = run(&dir, &check);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(findings(&output), ["NTS001 New"]);
+    assert!(
+        stderr(&output).contains("in `models.Old` is fixed; remove it from the baseline"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn stubs_and_typeshed_module_names() {
+    let dir = fixture("typeshed", "typeshed");
+    let output = run(&dir, &["check", "."]);
+    assert_eq!(
+        findings(&output),
+        [
+            "NTS001 Ordered",
+            "NTS001 ParseResult",
+            "NTS001 Request",
+            "NTS001 Point3D"
+        ]
+    );
+    let text = stdout(&output);
+    // `pairs.Pair` is a plain class at runtime; imports follow the stub.
+    assert!(text.contains("`Ordered` inherits from typed NamedTuple `pairs.Pair`"));
+    // Modules are named after `stdlib/` and `stubs/<distribution>/`.
+    assert!(text.contains(
+        "`Request` inherits from typed NamedTuple `urllib.parse._ResultBase` through `urllib.parse.ParseResult`"
+    ));
+    assert!(text.contains("`Point3D` inherits from typed NamedTuple `geo.shapes.Point`"));
+    assert_eq!(text.matches("  declared in a stub: ").count(), 3);
+}
+
+/// Every `# E` line of the conformance cases is an NTS001 finding, and every
+/// NTS001 finding is on an `# E` or `# E?` line.
+#[test]
+fn conformance_markers_match_findings() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("conformance");
+    let _ = fs::remove_dir_all(&dir);
+    let output = run(
+        Path::new(env!("CARGO_TARGET_TMPDIR")),
+        &["conformance", "conformance"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let mut required = BTreeSet::new();
+    let mut optional = BTreeSet::new();
+    for entry in fs::read_dir(dir.join("tests")).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        for (index, line) in fs::read_to_string(&path).unwrap().lines().enumerate() {
+            let Some((_, marker)) = line.rsplit_once("# E") else {
+                continue;
+            };
+            let at = (name.clone(), index + 1);
+            if marker.starts_with('?') {
+                optional.insert(at);
+            } else if marker.is_empty() || marker.starts_with(':') {
+                required.insert(at);
+            }
+        }
+    }
+
+    let output = run(&dir, &["check", "--select", "NTS001", "tests"]);
+    let reported: BTreeSet<(String, usize)> = stdout(&output)
+        .lines()
+        .filter(|line| line.contains(": NTS001 "))
+        .map(|line| {
+            let mut parts = line.split(':');
+            let path = parts.next().unwrap();
+            let name = Path::new(path).file_name().unwrap().to_string_lossy();
+            (name.into_owned(), parts.next().unwrap().parse().unwrap())
+        })
+        .collect();
+    assert!(!required.is_empty());
+    assert_eq!(
+        required.difference(&reported).collect::<Vec<_>>(),
+        Vec::<&(String, usize)>::new(),
+        "`# E` lines without a finding"
+    );
+    let unexpected: Vec<_> = reported
+        .iter()
+        .filter(|at| !required.contains(*at) && !optional.contains(*at))
+        .collect();
+    assert!(
+        unexpected.is_empty(),
+        "findings on unmarked lines: {unexpected:?}"
+    );
+}
+
+/// The reproducers built from the fixture's class statements behave as
+/// predicted on every local interpreter.
+#[test]
+fn verify_agrees_with_interpreters() {
+    let dir = fixture("multiple_inheritance", "verify");
+    let output = run(&dir, &["verify", "."]);
+    if stderr(&output).contains("no python3.x interpreter found") {
+        eprintln!("no Python interpreter, not verifying");
+        return;
+    }
+    let text = stdout(&output);
+    assert!(output.status.success(), "{text}{}", stderr(&output));
+    assert!(text.starts_with("Verified 5 findings on Python "), "{text}");
+    assert!(text.contains(": 0 mismatches.\n"), "{text}");
+    assert!(text.contains("Not verified: 4 NTS005 findings, which have no reproducer."));
+}
@@ -0,0 +1,5 @@
+import typing_extensions as te
+
+
+class Colour(te.NamedTuple):
+    r: int
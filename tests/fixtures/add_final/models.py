@@ -0,0 +1,10 @@
+from typing import NamedTuple, final
+
+
+class Point(NamedTuple):
+    x: int
+
+
+@final
+class Done(NamedTuple):
+    x: int
@@ -0,0 +1,5 @@
+from typing import NamedTuple, Optional
+
+
+class Pair(NamedTuple):
+    left: Optional[int]
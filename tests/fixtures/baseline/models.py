@@ -0,0 +1,13 @@
+from typing import NamedTuple
+
+
+class Point(NamedTuple):
+    x: int
+
+
+class Old(Point):
+    pass
+
+
+class Kept(Point):
+    pass
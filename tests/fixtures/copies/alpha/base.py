@@ -0,0 +1,9 @@
+from typing import NamedTuple
+
+
+class A(NamedTuple):
+    x: int
+
+
+class B(NamedTuple):
+    y: int
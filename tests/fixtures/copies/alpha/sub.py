@@ -0,0 +1,9 @@
+from base import A, B
+
+
+class X(A):
+    pass
+
+
+class Y(B):
+    pass
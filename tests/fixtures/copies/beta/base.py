@@ -0,0 +1,9 @@
+class A:
+    x = 0
+
+
+from typing import NamedTuple
+
+
+class B(NamedTuple):
+    y: int
@@ -0,0 +1,10 @@
+from base import A, B
+
+
+class X(A):  
+    pass
+
+
+class Y(B):
+    pass
+
@@ -0,0 +1,5 @@
+from typing import NamedTuple
+
+
+class Hidden(NamedTuple):
+    x: int
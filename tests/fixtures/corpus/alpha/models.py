@@ -0,0 +1,10 @@
+from typing import NamedTuple
+
+
+class Point(NamedTuple):
+    x: int
+    y: int
+
+
+class Point3D(Point):
+    z: int
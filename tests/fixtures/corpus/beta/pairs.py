@@ -0,0 +1,11 @@
+from typing import NamedTuple
+
+
+class Pair(NamedTuple):
+    left: int
+    right: int
+
+
+class Ordered(Pair):
+    def __lt__(self, other):
+        return self.left < other.left
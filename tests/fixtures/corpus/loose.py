@@ -0,0 +1,3 @@
+from typing import NamedTuple
+
+Colour = NamedTuple("Colour", [("r", int), ("g", int), ("b", int)])
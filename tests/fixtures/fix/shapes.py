@@ -0,0 +1,13 @@
+from typing import NamedTuple
+
+
+class Point(NamedTuple):
+    x: int
+    y: int
+
+    def norm(self):
+        return abs(self.x) + abs(self.y)
+
+
+class Point3D(Point):
+    z: int = 0
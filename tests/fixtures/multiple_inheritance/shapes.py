@@ -0,0 +1,49 @@
+from typing import NamedTuple
+
+
+class Point(NamedTuple):
+    x: int
+    y: int
+
+
+class Logged:
+    def __init__(self, *args):
+        print("created", args)
+
+
+class Replacing:
+    def __new__(cls, *args):
+        return tuple.__new__(cls)
+
+
+class Cooperative:
+    def __new__(cls, *args):
+        return super().__new__(cls, *args)
+
+
+class Tagged:
+    tag: str
+
+
+class Slotted:
+    __slots__ = ("a",)
+
+
+class LoggedPoint(Point, Logged):
+    pass
+
+
+class ReplacedPoint(Replacing, Point):
+    pass
+
+
+class CooperativePoint(Cooperative, Point):
+    pass
+
+
+class TaggedPoint(Point, Tagged):
+    pass
+
+
+class SlottedPoint(Point, Slotted):
+    pass
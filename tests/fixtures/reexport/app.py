@@ -0,0 +1,19 @@
+import pkg.models
+from pkg import Vector, models
+from pkg.models import *
+
+
+class Vector3D(Vector):
+    z: int = 0
+
+
+class Labelled(models.Point):
+    label: str = ""
+
+
+class Qualified(pkg.models.Point):
+    pass
+
+
+class Starred(Point):
+    pass
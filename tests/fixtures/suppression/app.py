@@ -0,0 +1,17 @@
+from models import Point
+
+
+class Accepted(Point):  # noqa: NTS001
+    pass
+
+
+class AcceptedAll(Point):  # noqa
+    pass
+
+
+class OtherCode(Point):  # noqa: NTS003
+    pass
+
+
+class Reported(Point):
+    pass
@@ -0,0 +1,6 @@
+# check-namedtuple: skip-file
+from models import Point
+
+
+class Skipped(Point):
+    pass
@@ -0,0 +1,5 @@
+from models import Point
+
+
+class InTests(Point):
+    pass
@@ -0,0 +1,5 @@
+from pairs import Pair
+
+
+class Ordered(Pair):
+    pass
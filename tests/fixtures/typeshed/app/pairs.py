@@ -0,0 +1,3 @@
+class Pair:
+    def __init__(self, left, right):
+        self.left, self.right = left, right
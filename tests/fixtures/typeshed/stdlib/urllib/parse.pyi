@@ -0,0 +1,8 @@
+from typing import NamedTuple
+
+class _ResultBase(NamedTuple):
+    scheme: str
+    netloc: str
+
+class ParseResult(_ResultBase):
+    def geturl(self) -> str: ...
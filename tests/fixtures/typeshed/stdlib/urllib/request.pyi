@@ -0,0 +1,3 @@
+from urllib.parse import ParseResult
+
+class Request(ParseResult): ...
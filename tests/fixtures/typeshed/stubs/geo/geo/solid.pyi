@@ -0,0 +1,4 @@
+from geo.shapes import Point
+
+class Point3D(Point):
+    z: float